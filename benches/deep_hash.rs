@@ -0,0 +1,76 @@
+use std::fs::{self, File};
+
+use bundlr_sdk::{
+    deep_hash::DeepHashChunk, deep_hash_sync::deep_hash_sync, tags::Tag, BundlrTx, Ed25519Signer,
+};
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const DATA_SIZE: usize = 32 * 1024 * 1024;
+const ITEM_PATH: &str = "./res/gen_bundles/bench_data_item";
+
+fn write_data_item() -> u64 {
+    let secret_key =
+        "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
+    let signer = Ed25519Signer::from_base58(secret_key).unwrap();
+    let mut data_item = BundlrTx::new(
+        vec![],
+        vec![0u8; DATA_SIZE],
+        vec![Tag::new("name", "value")],
+    )
+    .unwrap();
+    tokio_test::block_on(data_item.sign(&signer)).unwrap();
+
+    let bytes = data_item.as_bytes().unwrap();
+    fs::write(ITEM_PATH, &bytes).unwrap();
+    bytes.len() as u64
+}
+
+fn stream_chunk_size(c: &mut Criterion) {
+    let size = write_data_item();
+
+    let mut group = c.benchmark_group("stream_chunk_size");
+    group.throughput(Throughput::Bytes(size));
+    group.sample_size(10);
+    for chunk_size in [
+        16 * 1024,
+        64 * 1024,
+        256 * 1024,
+        1024 * 1024,
+        4 * 1024 * 1024,
+    ] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(chunk_size),
+            &chunk_size,
+            |b, &chunk_size| {
+                b.iter(|| {
+                    let mut file = File::open(ITEM_PATH).unwrap();
+                    let mut data_item = BundlrTx::from_file_position_with_chunk_size(
+                        &mut file, size, 0, 4096, chunk_size,
+                    )
+                    .unwrap();
+                    tokio_test::block_on(data_item.verify()).unwrap();
+                })
+            },
+        );
+    }
+    group.finish();
+
+    fs::remove_file(ITEM_PATH).unwrap();
+}
+
+// Compare runs with and without `--features ring-sha` to measure the SHA-384 backends
+fn sha384_backend(c: &mut Criterion) {
+    let data = Bytes::from(vec![0u8; DATA_SIZE]);
+
+    let mut group = c.benchmark_group("sha384_backend");
+    group.throughput(Throughput::Bytes(DATA_SIZE as u64));
+    group.sample_size(10);
+    group.bench_function("deep_hash_sync", |b| {
+        b.iter(|| deep_hash_sync(DeepHashChunk::Chunk(data.clone())).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, stream_chunk_size, sha384_backend);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+use std::fs::{self, File};
+
+use bundlr_sdk::{
+    tags::Tag,
+    test_support::{make_bundle, make_item},
+    verify::file::verify_file_bundle,
+    BundlrTx, Ed25519Signer,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use primitive_types::U256;
+
+const ITEMS: usize = 100_000;
+const BUNDLE_PATH: &str = "./res/gen_bundles/bench_tiny_items_bundle";
+
+fn write_bundle() {
+    let secret_key =
+        "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
+    let signer = Ed25519Signer::from_base58(secret_key).unwrap();
+    let tags = vec![
+        Tag::new("name", "value"),
+        Tag::new("Content-Type", "text/plain"),
+    ];
+
+    let items = (0..ITEMS)
+        .map(|i| tokio_test::block_on(make_item(&signer, i.to_string().as_bytes(), &tags)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    fs::write(BUNDLE_PATH, make_bundle(&items).unwrap()).unwrap();
+}
+
+/// Verification loop used before small items got their in-memory path: every item is loaded
+/// with `from_file_position` and its data streamed through the async deep hash.
+fn verify_with_file_position(path: &str) -> usize {
+    let mut file = File::open(path).unwrap();
+    let bytes = fs::read(path).unwrap();
+    let count = U256::from_little_endian(&bytes[..32]).as_u64() as usize;
+
+    let mut offset = 32 + 64 * count as u64;
+    for i in 0..count {
+        let header = 32 + 64 * i;
+        let size = U256::from_little_endian(&bytes[header..header + 32]).as_u64();
+        let mut tx = BundlrTx::from_file_position(&mut file, size, offset, 4096).unwrap();
+        tokio_test::block_on(tx.verify()).unwrap();
+        offset += size;
+    }
+    count
+}
+
+fn tiny_items(c: &mut Criterion) {
+    write_bundle();
+
+    let mut group = c.benchmark_group("verify_tiny_items");
+    group.throughput(Throughput::Elements(ITEMS as u64));
+    group.sample_size(10);
+    group.bench_function("from_file_position", |b| {
+        b.iter(|| verify_with_file_position(BUNDLE_PATH))
+    });
+    group.bench_function("verify_file_bundle", |b| {
+        b.iter(|| tokio_test::block_on(verify_file_bundle(BUNDLE_PATH.to_string())).unwrap())
+    });
+    group.finish();
+
+    fs::remove_file(BUNDLE_PATH).unwrap();
+}
+
+criterion_group!(benches, tiny_items);
+criterion_main!(benches);
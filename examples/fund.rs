@@ -5,7 +5,7 @@ use reqwest::Url;
 
 #[tokio::main]
 async fn main() -> Result<(), BundlrError> {
-    let url = Url::parse("https://node1.bundlr.network").unwrap();
+    let url = Url::parse("https://node1.irys.xyz").unwrap();
     let wallet = PathBuf::from_str("res/test_wallet.json").unwrap();
     let currency = ArweaveBuilder::new()
         .keypair_path(wallet)
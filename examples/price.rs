@@ -3,7 +3,7 @@ use reqwest::Url;
 
 #[tokio::main]
 async fn main() {
-    let url = Url::parse("https://node1.bundlr.network").unwrap();
+    let url = Url::parse("https://node1.irys.xyz").unwrap();
     let currency = CurrencyType::Solana;
 
     let client = reqwest::Client::new();
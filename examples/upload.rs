@@ -1,11 +1,14 @@
 use std::{path::PathBuf, str::FromStr};
 
-use bundlr_sdk::{bundlr::BundlrBuilder, currency::solana::SolanaBuilder, error::BundlrError};
+use bundlr_sdk::{
+    bundlr::BundlrBuilder, currency::solana::SolanaBuilder, error::BundlrError,
+    tags::FileMetadataTags,
+};
 use reqwest::Url;
 
 #[tokio::main]
 async fn main() -> Result<(), BundlrError> {
-    let url = Url::parse("https://node1.bundlr.network").unwrap();
+    let url = Url::parse("https://node1.irys.xyz").unwrap();
     let currency = SolanaBuilder::new().wallet(
         "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb")
         .build()
@@ -18,7 +21,7 @@ async fn main() -> Result<(), BundlrError> {
         .build()?;
 
     let file = PathBuf::from_str("res/test_image.jpg").unwrap();
-    let res = bundlr.upload_file(file).await;
+    let res = bundlr.upload_file_with_metadata(file, FileMetadataTags::Include).await;
     match res {
         Ok(()) => println!("[ok]"),
         Err(err) => println!("[err] {}", err),
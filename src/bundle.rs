@@ -0,0 +1,525 @@
+#[cfg(feature = "compression")]
+use std::io::Write;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use bytes::BufMut;
+use data_encoding::BASE64URL_NOPAD;
+#[cfg(feature = "compression")]
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use primitive_types::U256;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    consts::{COMPRESSED_BUNDLE_MAGIC, L1_SIGNATURE_LENGTH, MAX_DECOMPRESSED_SECTION_SIZE},
+    error::BundlrError,
+    utils::{read_offset, u256_le},
+    BundlrTx,
+};
+
+/// Leading section of a file written by [`write_bundle_compressed`], decompressed
+pub(crate) struct CompressedSection {
+    /// Item count and header table, followed by the items when `data_compressed` is set
+    pub bytes: Vec<u8>,
+    pub data_compressed: bool,
+    /// Offset in the file of the uncompressed items, when `data_compressed` isn't set
+    pub data_offset: u64,
+}
+
+/// Writes signed `items` as an ANS-104 bundle: item count, header table, then every item.
+/// The data of file-backed items is streamed, so the bundle never has to fit in memory.
+pub async fn write_bundle<W: AsyncWrite + Unpin>(
+    items: Vec<BundlrTx>,
+    writer: &mut W,
+) -> Result<(), BundlrError> {
+    writer.write_all(&header_table(&items)?).await?;
+    for mut item in items {
+        item.write_to(writer).await?;
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Same as [`write_bundle`], but the item count and header table are gzip-compressed, along
+/// with the items when `compress_data` is set. The output starts with
+/// [`COMPRESSED_BUNDLE_MAGIC`], a flag byte telling whether the items are compressed, and the
+/// little-endian `u64` length of the compressed section. Uncompressed items follow that section.
+///
+/// Items are only streamed when `compress_data` isn't set, the whole bundle is held in memory
+/// otherwise. Such files can be verified with [`crate::verify::file::verify_file_bundle`] and
+/// turned back into a plain bundle with [`read_bundle_compressed`].
+#[cfg(feature = "compression")]
+pub async fn write_bundle_compressed<W: AsyncWrite + Unpin>(
+    items: Vec<BundlrTx>,
+    writer: &mut W,
+    compress_data: bool,
+) -> Result<(), BundlrError> {
+    let (section, items) = if compress_data {
+        let mut bundle = vec![];
+        write_bundle(items, &mut bundle).await?;
+        (bundle, vec![])
+    } else {
+        (header_table(&items)?, items)
+    };
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&section)?;
+    let compressed = encoder.finish()?;
+
+    writer.write_all(COMPRESSED_BUNDLE_MAGIC).await?;
+    writer.write_all(&[compress_data as u8]).await?;
+    writer
+        .write_all(&(compressed.len() as u64).to_le_bytes())
+        .await?;
+    writer.write_all(&compressed).await?;
+    for mut item in items {
+        item.write_to(writer).await?;
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads the bundle in `file` into memory, decompressing it if it was written by
+/// [`write_bundle_compressed`]. Plain bundles are returned as they are.
+#[cfg(feature = "compression")]
+pub fn read_bundle_compressed(file: &mut File) -> Result<Vec<u8>, BundlrError> {
+    let mut bundle = vec![];
+    match read_compressed_section(file)? {
+        Some(section) if section.data_compressed => return Ok(section.bytes),
+        Some(section) => {
+            bundle = section.bytes;
+            file.seek(SeekFrom::Start(section.data_offset))?;
+        }
+        None => {
+            file.seek(SeekFrom::Start(0))?;
+        }
+    }
+    file.read_to_end(&mut bundle)?;
+    Ok(bundle)
+}
+
+/// Reads and decompresses the leading section of `file` if it was written by
+/// `write_bundle_compressed`, or returns `None` for plain bundles. Without the `compression`
+/// feature, such files fail with `BundlrError::Unsupported`.
+pub(crate) fn read_compressed_section(
+    file: &mut File,
+) -> Result<Option<CompressedSection>, BundlrError> {
+    read_compressed_section_with_limit(file, MAX_DECOMPRESSED_SECTION_SIZE)
+}
+
+/// Same as [`read_compressed_section`], but fails with `BundlrError::Unsupported` once the
+/// section decompresses to more than `limit` bytes
+fn read_compressed_section_with_limit(
+    file: &mut File,
+    limit: u64,
+) -> Result<Option<CompressedSection>, BundlrError> {
+    let file_len = file.metadata()?.len();
+    let prefix_len = COMPRESSED_BUNDLE_MAGIC.len() + 9;
+    if file_len < prefix_len as u64 {
+        return Ok(None);
+    }
+    let prefix = read_offset(file, 0, prefix_len)?;
+    let (magic, prefix) = prefix.split_at(COMPRESSED_BUNDLE_MAGIC.len());
+    if magic != COMPRESSED_BUNDLE_MAGIC {
+        return Ok(None);
+    }
+
+    let data_compressed = match prefix[0] {
+        0 => false,
+        1 => true,
+        _ => return Err(BundlrError::InvalidHeaders),
+    };
+    let mut compressed_len = [0u8; 8];
+    compressed_len.copy_from_slice(&prefix[1..]);
+    let compressed_len = u64::from_le_bytes(compressed_len);
+    let data_offset = (prefix_len as u64)
+        .checked_add(compressed_len)
+        .filter(|offset| *offset <= file_len)
+        .ok_or(BundlrError::InvalidHeaders)?;
+
+    file.seek(SeekFrom::Start(prefix_len as u64))?;
+    Ok(Some(CompressedSection {
+        bytes: decompress(file.take(compressed_len), limit)?,
+        data_compressed,
+        data_offset,
+    }))
+}
+
+/// Gunzips `compressed`, failing with `BundlrError::Unsupported` once it decompresses to more
+/// than `limit` bytes
+#[cfg(feature = "compression")]
+fn decompress(compressed: impl Read, limit: u64) -> Result<Vec<u8>, BundlrError> {
+    let mut bytes = vec![];
+    GzDecoder::new(compressed)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(BundlrError::Unsupported(format!(
+            "compressed sections over {} bytes",
+            limit
+        )));
+    }
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_compressed: impl Read, _limit: u64) -> Result<Vec<u8>, BundlrError> {
+    Err(BundlrError::Unsupported(
+        "compressed bundles without the compression feature".to_owned(),
+    ))
+}
+
+/// Item count followed by the header table of `items`, i.e. the size and id of every item
+fn header_table(items: &[BundlrTx]) -> Result<Vec<u8>, BundlrError> {
+    let mut table = Vec::with_capacity(32 + 64 * items.len());
+    table.put(&u256_le(items.len() as u64)[..]);
+    for item in items {
+        table.put(&u256_le(item.size()?)[..]);
+        table.put(&Sha256::digest(item.get_signarure())[..]);
+    }
+    Ok(table)
+}
+
+/// Id of the L1 transaction posting a bundle, computed from the `signature` of that transaction
+/// the way the network does, i.e. the base64url SHA-256 of the signature. The id can't be derived
+/// from the bundle alone, as the signature also covers transaction fields the bundle doesn't
+/// hold, like the owner and the anchor. Fails with `BundlrError::InvalidSignature` if
+/// `signature` isn't an L1 signature.
+pub fn bundle_id(signature: &[u8]) -> Result<String, BundlrError> {
+    if signature.len() != L1_SIGNATURE_LENGTH {
+        return Err(BundlrError::InvalidSignature);
+    }
+    Ok(BASE64URL_NOPAD.encode(&Sha256::digest(signature)))
+}
+
+/// Merges the bundles at `paths` into a single bundle holding all of their items, in order.
+/// Items are copied as they are, without being verified, but the merge fails with
+/// `BundlrError::InvalidHeaders` if a bundle is malformed or if two items share the same id.
+pub async fn merge_bundles(paths: &[String]) -> Result<Vec<u8>, BundlrError> {
+    let mut ids = HashSet::new();
+    let mut headers = vec![];
+    let mut items = vec![];
+
+    for path in paths {
+        let bundle = tokio::fs::read(path).await?;
+        let count = U256::from_little_endian(bundle.get(..32).ok_or(BundlrError::NoBytesLeft)?);
+        if count > U256::from((bundle.len() - 32) / 64) {
+            return Err(BundlrError::InvalidHeaders);
+        }
+        let count = count.as_usize();
+
+        let mut offset = 32 + 64 * count;
+        for i in 0..count {
+            let header = &bundle[32 + 64 * i..32 + 64 * (i + 1)];
+            if !ids.insert(header[32..].to_vec()) {
+                return Err(BundlrError::InvalidHeaders);
+            }
+            let size = U256::from_little_endian(&header[..32]);
+            if size > U256::from(bundle.len() - offset) {
+                return Err(BundlrError::InvalidHeaders);
+            }
+            let size = size.as_usize();
+
+            headers.put(header);
+            items.put(&bundle[offset..offset + size]);
+            offset += size;
+        }
+        if offset != bundle.len() {
+            return Err(BundlrError::InvalidHeaders);
+        }
+    }
+
+    let mut merged = Vec::with_capacity(32 + headers.len() + items.len());
+    merged.put(&u256_le(ids.len() as u64)[..]);
+    merged.put(&headers[..]);
+    merged.put(&items[..]);
+    Ok(merged)
+}
+
+/// Reads the item with the base64url encoded `id` out of the bundle in `file`, without reading
+/// the other items. Returns `BundlrError::TxNotFound` if the bundle has no such item.
+pub fn extract_item(file: &mut File, id: &str) -> Result<Vec<u8>, BundlrError> {
+    let id = BASE64URL_NOPAD
+        .decode(id.trim_end_matches('=').as_bytes())
+        .map_err(|err| BundlrError::Base64Error(err.to_string()))?;
+
+    let file_len = file.metadata()?.len();
+    let count = U256::from_little_endian(&read_offset(file, 0, 32)?);
+    // The item count and the header table must both fit in the file
+    if count > U256::from(file_len.saturating_sub(32) / 64) {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    let count = usize::try_from(count.as_u64()).map_err(|_| BundlrError::InvalidHeaders)?;
+    let headers = read_offset(file, 32, 64 * count)?;
+
+    let mut offset = 32 + 64 * count as u64;
+    for header in headers.chunks(64) {
+        let size = U256::from_little_endian(&header[..32]);
+        if size > U256::from(u64::MAX) {
+            return Err(BundlrError::InvalidHeaders);
+        }
+        let size = size.as_u64();
+        let end = offset
+            .checked_add(size)
+            .ok_or(BundlrError::InvalidHeaders)?;
+        if header[32..] == id[..] {
+            if end > file_len {
+                return Err(BundlrError::NoBytesLeft);
+            }
+            let size = usize::try_from(size).map_err(|_| BundlrError::InvalidHeaders)?;
+            let item = read_offset(file, offset, size)?;
+            return Ok(item.to_vec());
+        }
+        offset = end;
+    }
+    Err(BundlrError::TxNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    #[cfg(feature = "compression")]
+    use std::io::Write;
+
+    #[cfg(feature = "compression")]
+    use flate2::{write::GzEncoder, Compression};
+    #[cfg(feature = "compression")]
+    use sha2::{Digest, Sha256};
+
+    use super::{bundle_id, extract_item, merge_bundles, write_bundle};
+    #[cfg(feature = "compression")]
+    use super::{
+        read_bundle_compressed, read_compressed_section_with_limit, write_bundle_compressed,
+    };
+    use crate::{
+        consts::COMPRESSED_BUNDLE_MAGIC,
+        error::BundlrError,
+        tags::Tag,
+        test_support::{make_item, temp_path, test_signer},
+        utils::u256_le,
+        verify::file::verify_file_bundle,
+        BundlrTx,
+    };
+
+    #[test]
+    fn should_compute_bundle_id_from_signature() {
+        assert_eq!(
+            bundle_id(&[7u8; 512]).unwrap(),
+            "FZMwRJYP0jp9qqyc5RNV8fOYlNHD_m3iG1myjOLHfnc"
+        );
+        assert!(matches!(
+            bundle_id(&[7u8; 64]),
+            Err(BundlrError::InvalidSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_write_verifiable_bundle() -> Result<(), BundlrError> {
+        let item_path = &temp_path("test_write_bundle_item");
+        let bundle_path = &temp_path("test_write_bundle");
+        let signer = test_signer();
+
+        let mut in_memory =
+            BundlrTx::new(vec![], b"hello".to_vec(), vec![Tag::new("name", "value")])?;
+        in_memory.sign(&signer).await?;
+
+        let on_disk = make_item(&signer, &[7u8; 100_000], &[]).await?;
+        std::fs::write(item_path, &on_disk)?;
+        let mut file = File::open(item_path)?;
+        let streamed = BundlrTx::from_file_position(&mut file, on_disk.len() as u64, 0, 4096)?;
+
+        let mut writer = tokio::fs::File::create(bundle_path).await?;
+        write_bundle(vec![in_memory, streamed], &mut writer).await?;
+
+        let items = verify_file_bundle(bundle_path.to_string()).await?;
+        assert_eq!(items.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_merge_bundles() -> Result<(), BundlrError> {
+        let path = &temp_path("test_merged_bundle");
+        let solana = "./res/test_bundles/solana_sig".to_string();
+        let ethereum = "./res/test_bundles/ethereum_sig".to_string();
+
+        let merged = merge_bundles(&[solana.clone(), ethereum.clone()]).await?;
+        std::fs::write(path, merged)?;
+
+        let items = verify_file_bundle(path.to_string()).await?;
+        let solana_items = verify_file_bundle(solana.clone()).await?;
+        let ethereum_items = verify_file_bundle(ethereum).await?;
+        assert_eq!(items.len(), solana_items.len() + ethereum_items.len());
+
+        assert!(matches!(
+            merge_bundles(&[solana.clone(), solana]).await,
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_extract_item() -> Result<(), BundlrError> {
+        let path = "./res/test_bundles/ethereum_sig";
+        let items = verify_file_bundle(path.to_string()).await?;
+        let mut file = File::open(path)?;
+
+        let item = extract_item(&mut file, &items[0].tx_id)?;
+        let mut tx = BundlrTx::from_bytes(item)?;
+        tx.verify().await?;
+        assert_eq!(tx.get_signarure(), items[0].signature);
+
+        assert!(matches!(
+            extract_item(&mut file, &"A".repeat(43)),
+            Err(BundlrError::TxNotFound)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_out_of_range_headers() -> Result<(), BundlrError> {
+        let path = &temp_path("test_extract_bad_headers");
+        let id = [1u8; 32];
+        let encoded_id = "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE";
+        let header = |size: [u8; 32]| [&size[..], &id[..]].concat();
+
+        // One header, but no room left for it after the item count
+        std::fs::write(path, [&u256_le(1)[..], &[0u8; 63][..]].concat())?;
+        assert!(matches!(
+            extract_item(&mut File::open(path)?, encoded_id),
+            Err(BundlrError::InvalidHeaders)
+        ));
+
+        // Item size that doesn't fit in 64 bits
+        let mut size = [0u8; 32];
+        size[8] = 1;
+        std::fs::write(path, [&u256_le(1)[..], &header(size)[..]].concat())?;
+        assert!(matches!(
+            extract_item(&mut File::open(path)?, encoded_id),
+            Err(BundlrError::InvalidHeaders)
+        ));
+
+        // Item offset overflowing once added to the size of a previous item
+        let table = [
+            &u256_le(2)[..],
+            &header(u256_le(u64::MAX))[..],
+            &header(u256_le(1))[..],
+        ]
+        .concat();
+        std::fs::write(path, table)?;
+        assert!(matches!(
+            extract_item(&mut File::open(path)?, &"A".repeat(43)),
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "compression")]
+    async fn should_verify_compressed_bundle() -> Result<(), BundlrError> {
+        let fixture = "./res/test_bundles/ethereum_sig";
+        let expected = verify_file_bundle(fixture.to_string()).await?;
+        let mut file = File::open(fixture)?;
+
+        for (path, compress_data) in [
+            (temp_path("test_compressed_headers"), false),
+            (temp_path("test_compressed_bundle"), true),
+        ] {
+            let items = expected
+                .iter()
+                .map(|item| BundlrTx::from_bytes(extract_item(&mut file, &item.tx_id)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut writer = tokio::fs::File::create(&path).await?;
+            write_bundle_compressed(items, &mut writer, compress_data).await?;
+
+            let items = verify_file_bundle(path.to_string()).await?;
+            assert_eq!(items.len(), expected.len());
+            for (item, expected) in items.iter().zip(&expected) {
+                assert_eq!(item.tx_id, expected.tx_id);
+                assert_eq!(item.signature, expected.signature);
+            }
+            assert_eq!(
+                read_bundle_compressed(&mut File::open(path)?)?,
+                std::fs::read(fixture)?
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "compression")]
+    async fn should_bound_decompressed_section() -> Result<(), BundlrError> {
+        let path = &temp_path("test_compressed_zeros");
+        let signer = test_signer();
+        let mut item = BundlrTx::new(vec![], vec![0u8; 100_000], vec![])?;
+        item.sign(&signer).await?;
+        let mut writer = tokio::fs::File::create(path).await?;
+        write_bundle_compressed(vec![item], &mut writer, true).await?;
+
+        // Zeros compress well, so a small file can hold a much larger section
+        let mut file = File::open(path)?;
+        assert!(file.metadata()?.len() < 10_000);
+        assert!(matches!(
+            read_compressed_section_with_limit(&mut file, 10_000),
+            Err(BundlrError::Unsupported(_))
+        ));
+        assert!(read_compressed_section_with_limit(&mut file, 1_000_000)?.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "compression")]
+    async fn should_parse_compressed_items() -> Result<(), BundlrError> {
+        let path = &temp_path("test_compressed_tag_count");
+        let signer = test_signer();
+        let mut item = BundlrTx::new(vec![], b"hello".to_vec(), vec![Tag::new("name", "value")])?;
+        item.sign(&signer).await?;
+        let id = Sha256::digest(item.get_signarure());
+        let mut bytes = item.as_bytes()?;
+        // The tag count, after the signature type, signature, owner, and presence bytes of the
+        // missing target and of the anchor, isn't covered by the signature
+        bytes[132..140].copy_from_slice(&2u64.to_le_bytes());
+
+        let mut section = vec![];
+        section.extend_from_slice(&u256_le(1));
+        section.extend_from_slice(&u256_le(bytes.len() as u64));
+        section.extend_from_slice(&id);
+        section.extend_from_slice(&bytes);
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&section)?;
+        let compressed = encoder.finish()?;
+
+        let mut file = COMPRESSED_BUNDLE_MAGIC.to_vec();
+        file.push(1);
+        file.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+        file.extend_from_slice(&compressed);
+        std::fs::write(path, file)?;
+
+        let res = verify_file_bundle(path.to_string()).await;
+        assert!(
+            matches!(res, Err(BundlrError::InvalidTagEncoding)),
+            "{:?}",
+            res
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "compression"))]
+    async fn should_reject_compressed_bundle_without_feature() -> Result<(), BundlrError> {
+        let path = &temp_path("test_compressed_unsupported");
+        let mut file = COMPRESSED_BUNDLE_MAGIC.to_vec();
+        file.push(0);
+        file.extend_from_slice(&0u64.to_le_bytes());
+        std::fs::write(path, file)?;
+
+        assert!(matches!(
+            verify_file_bundle(path.to_string()).await,
+            Err(BundlrError::Unsupported(_))
+        ));
+        Ok(())
+    }
+}
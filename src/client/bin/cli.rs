@@ -1,11 +1,16 @@
-use std::time::Duration;
+use std::{future::Future, process, time::Duration};
 
 use bundlr_sdk::{
     client::{
-        balance::run_balance, fund::run_fund, price::run_price, upload::run_upload,
+        balance::run_balance,
+        exit_code::{exit_code, NETWORK, SUCCESS},
+        fund::run_fund,
+        price::run_price,
+        upload::run_upload,
         withdraw::run_withdraw,
     },
     currency::CurrencyType,
+    error::BundlrError,
 };
 use clap::{Parser, Subcommand};
 use reqwest::Url;
@@ -127,8 +132,31 @@ enum Command {
     },
 }
 
+/// Runs a command, printing its outcome, and returns the exit code for it
+async fn run_with_timeout(
+    work: impl Future<Output = Result<String, BundlrError>>,
+    timeout: u64,
+) -> i32 {
+    match tokio::time::timeout(Duration::from_millis(timeout), work).await {
+        Ok(res) => match res {
+            Ok(ok) => {
+                println!("[Ok] {}", ok);
+                SUCCESS
+            }
+            Err(err) => {
+                println!("[Err] {}", err);
+                exit_code(&err)
+            }
+        },
+        Err(err) => {
+            println!("Error running task: {}", err);
+            NETWORK
+        }
+    }
+}
+
 impl Command {
-    async fn execute(self) {
+    async fn execute(self) -> i32 {
         match self {
             Command::Balance {
                 address,
@@ -138,13 +166,7 @@ impl Command {
             } => {
                 let work = run_balance(host, &address, currency);
                 let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT);
-                match tokio::time::timeout(Duration::from_millis(timeout), work).await {
-                    Ok(res) => match res {
-                        Ok(ok) => println!("[Ok] {}", ok),
-                        Err(err) => println!("[Err] {}", err),
-                    },
-                    Err(err) => println!("Error running task: {}", err),
-                }
+                run_with_timeout(work, timeout).await
             }
             Command::Fund {
                 amount,
@@ -155,13 +177,7 @@ impl Command {
             } => {
                 let work = run_fund(amount, host, &wallet, currency);
                 let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT_FUND);
-                match tokio::time::timeout(Duration::from_millis(timeout), work).await {
-                    Ok(res) => match res {
-                        Ok(ok) => println!("[Ok] {}", ok),
-                        Err(err) => println!("[Err] {}", err),
-                    },
-                    Err(err) => println!("Error running task: {}", err),
-                }
+                run_with_timeout(work, timeout).await
             }
             Command::Withdraw {
                 amount,
@@ -172,13 +188,7 @@ impl Command {
             } => {
                 let work = run_withdraw(amount, host, &wallet, currency);
                 let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT);
-                match tokio::time::timeout(Duration::from_millis(timeout), work).await {
-                    Ok(res) => match res {
-                        Ok(ok) => println!("[Ok] {}", ok),
-                        Err(err) => println!("[Err] {}", err),
-                    },
-                    Err(err) => println!("Error running task: {}", err),
-                }
+                run_with_timeout(work, timeout).await
             }
             Command::Upload {
                 file_path,
@@ -189,13 +199,7 @@ impl Command {
             } => {
                 let work = run_upload(file_path, host, &wallet, currency);
                 let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT);
-                match tokio::time::timeout(Duration::from_millis(timeout), work).await {
-                    Ok(res) => match res {
-                        Ok(ok) => println!("[Ok] {}", ok),
-                        Err(err) => println!("[Err] {}", err),
-                    },
-                    Err(err) => println!("Error running task: {}", err),
-                }
+                run_with_timeout(work, timeout).await
             }
             Command::UploadDir {} => todo!(),
             Command::Price {
@@ -207,13 +211,7 @@ impl Command {
                 let byte_amount = byte_amount.unwrap_or(DEFAULT_BYTE_AMOUNT);
                 let work = run_price(host, currency, byte_amount);
                 let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT);
-                match tokio::time::timeout(Duration::from_millis(timeout), work).await {
-                    Ok(res) => match res {
-                        Ok(ok) => println!("[Ok] {}", ok),
-                        Err(err) => println!("[Err] {}", err),
-                    },
-                    Err(err) => println!("Error running task: {}", err),
-                }
+                run_with_timeout(work, timeout).await
             }
         }
     }
@@ -223,5 +221,6 @@ impl Command {
 async fn main() {
     let args = Args::parse();
 
-    args.command.execute().await;
+    let code = args.command.execute().await;
+    process::exit(code);
 }
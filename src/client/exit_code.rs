@@ -0,0 +1,90 @@
+use crate::error::{BuilderError, BundlrError};
+
+/// The command succeeded
+pub const SUCCESS: i32 = 0;
+/// The node or a currency network could not be reached, or answered with an error
+pub const NETWORK: i32 = 1;
+/// A key, signature or signer could not be used or did not verify
+pub const SIGNATURE: i32 = 2;
+/// The input or some received data is malformed or not accepted
+pub const VALIDATION: i32 = 3;
+/// Any other failure, like file system errors
+pub const OTHER: i32 = 4;
+
+/// Maps an error to the process exit code the cli terminates with
+pub fn exit_code(err: &BundlrError) -> i32 {
+    match err {
+        BundlrError::ResponseError(_)
+        | BundlrError::RequestError(_)
+        | BundlrError::UploadError(_)
+        | BundlrError::PostChunkError(_)
+        | BundlrError::TooManyRequests
+        | BundlrError::TxNotFound
+        | BundlrError::TxStatusNotConfirmed
+        | BundlrError::ArweaveSdkError(_) => NETWORK,
+
+        BundlrError::InvalidSignerType
+        | BundlrError::InvalidSignature
+        | BundlrError::InvalidKey(_)
+        | BundlrError::SigningError(_)
+        | BundlrError::NoSignature
+        | BundlrError::CurrencyError(_)
+        | BundlrError::ED25519Error(_)
+        | BundlrError::Secp256k1Error(_)
+        | BundlrError::Eip712Error(_)
+        | BundlrError::RecoveryError(_) => SIGNATURE,
+
+        BundlrError::InvalidHeaders
+        | BundlrError::InvalidPresenceByte(_)
+        | BundlrError::NoBytesLeft
+        | BundlrError::InvalidTagEncoding
+        | BundlrError::InvalidFundingValue
+        | BundlrError::InvalidAmount
+        | BundlrError::InvalidCurrency(_)
+        | BundlrError::ChunkSizeOutOfRange(_, _)
+        | BundlrError::InvalidChunkSize(_)
+        | BundlrError::InvalidDataType
+        | BundlrError::BytesError(_)
+        | BundlrError::TypeParseError(_)
+        | BundlrError::ParseError(_)
+        | BundlrError::Base64Error(_)
+        | BundlrError::ItemTooLarge
+        | BundlrError::Expired(_)
+        | BundlrError::DigestMismatch(_)
+        | BundlrError::Unsupported(_) => VALIDATION,
+
+        BundlrError::BuilderError(err) => match err {
+            BuilderError::FetchPubInfoError(_) | BuilderError::ArweaveSdkError(_) => NETWORK,
+            BuilderError::MissingField(_) | BuilderError::IncompatibleNodeVersion { .. } => {
+                VALIDATION
+            }
+            BuilderError::BundlrError(_) => OTHER,
+        },
+
+        BundlrError::FsError(_) | BundlrError::IoError(_) | BundlrError::Unknown(_) => OTHER,
+
+        BundlrError::Context { source, .. } => exit_code(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exit_code, NETWORK, OTHER, SIGNATURE, VALIDATION};
+    use crate::error::{BuilderError, BundlrError};
+
+    #[test]
+    fn should_map_errors_to_exit_codes() {
+        assert_eq!(exit_code(&BundlrError::ResponseError("".into())), NETWORK);
+        assert_eq!(exit_code(&BundlrError::InvalidSignature), SIGNATURE);
+        assert_eq!(exit_code(&BundlrError::InvalidKey("".into())), SIGNATURE);
+        assert_eq!(exit_code(&BundlrError::InvalidAmount), VALIDATION);
+        assert_eq!(exit_code(&BundlrError::InvalidChunkSize(0)), VALIDATION);
+        assert_eq!(exit_code(&BundlrError::FsError("".into())), OTHER);
+        assert_eq!(
+            exit_code(&BundlrError::BuilderError(BuilderError::FetchPubInfoError(
+                "".into()
+            ))),
+            NETWORK
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::{path::PathBuf, str::FromStr};
 use crate::{
     bundlr::BundlrBuilder,
     consts::USE_JS_SDK,
-    currency::{arweave::ArweaveBuilder, CurrencyType},
+    currency::{arweave::ArweaveBuilder, Currency, CurrencyType},
     error::BundlrError,
 };
 use num_traits::Zero;
@@ -23,13 +23,21 @@ pub async fn run_fund(
     match currency {
         CurrencyType::Arweave => {
             let currency = ArweaveBuilder::new().keypair_path(wallet).build()?;
+            // The funding transaction was just sent, so it needs every confirmation
+            let eta = currency.block_time() * currency.min_confirmations() as u32;
             let bundlr = BundlrBuilder::new()
                 .url(url)
                 .currency(currency)
                 .fetch_pub_info()
                 .await?
                 .build()?;
-            bundlr.fund(amount, None).await.map(|res| res.to_string())
+            bundlr.fund(amount, None).await.map(|res| {
+                format!(
+                    "{} (confirmation expected in ~{} minutes)",
+                    res,
+                    eta.as_secs().div_ceil(60)
+                )
+            })
         }
         CurrencyType::Solana => todo!("{}", USE_JS_SDK),
         CurrencyType::Ethereum => todo!("{}", USE_JS_SDK),
@@ -1,4 +1,5 @@
 pub mod balance;
+pub mod exit_code;
 pub mod fund;
 pub mod method;
 pub mod price;
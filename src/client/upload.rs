@@ -46,7 +46,7 @@ pub async fn run_upload(
             let sig = bundlr.sign_transaction(&mut tx).await;
             assert!(sig.is_ok());
             match bundlr.send_transaction(tx).await {
-                Ok(res) => Ok(format!("File {} uploaded: {}", file_path, res)),
+                Ok(res) => Ok(format!("File {} uploaded: {}", file_path, res.id)),
                 Err(err) => Err(BundlrError::UploadError(err.to_string())),
             }
         }
@@ -62,7 +62,7 @@ pub async fn run_upload(
             let sig = bundlr.sign_transaction(&mut tx).await;
             assert!(sig.is_ok());
             match bundlr.send_transaction(tx).await {
-                Ok(res) => Ok(format!("File {} uploaded: {}", file_path, res)),
+                Ok(res) => Ok(format!("File {} uploaded: {}", file_path, res.id)),
                 Err(err) => Err(BundlrError::UploadError(err.to_string())),
             }
         }
@@ -78,7 +78,7 @@ pub async fn run_upload(
             let sig = bundlr.sign_transaction(&mut tx).await;
             assert!(sig.is_ok());
             match bundlr.send_transaction(tx).await {
-                Ok(res) => Ok(format!("File {} uploaded: {}", file_path, res)),
+                Ok(res) => Ok(format!("File {} uploaded: {}", file_path, res.id)),
                 Err(err) => Err(BundlrError::UploadError(err.to_string())),
             }
         }
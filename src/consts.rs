@@ -1,7 +1,22 @@
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub const BUNDLR_DEFAULT_URL: &str = "https://node1.bundlr.network/";
+pub const DEFAULT_BUNDLER_URL: &str = "https://uploader.irys.xyz/";
+#[deprecated(note = "Bundlr is now Irys, use `DEFAULT_BUNDLER_URL`")]
+pub const BUNDLR_DEFAULT_URL: &str = DEFAULT_BUNDLER_URL;
+/// Hosts of the deprecated Bundlr network nodes, along with their Irys equivalent
+pub const LEGACY_BUNDLR_HOSTS: &[(&str, &str)] = &[
+    ("node1.bundlr.network", "node1.irys.xyz"),
+    ("node2.bundlr.network", "node2.irys.xyz"),
+    ("devnet.bundlr.network", "devnet.irys.xyz"),
+];
+pub const PRICE_ORACLE_DEFAULT_URL: &str = "https://api.coingecko.com/api/v3/";
+/// Header asking the node to return a signed receipt for an uploaded transaction
+pub const RECEIPT_REQUEST_HEADER: &str = "x-irys-receipt";
+/// Maximum number of tags of an item allowed by ANS-104
+pub const MAX_TAGS: usize = 128;
 pub const CHUNK_SIZE: u64 = 256u64 * 1024;
+/// Version of the chunked upload protocol sent in the `x-chunking-version` header
+pub const CHUNKING_VERSION: u16 = 2;
 /// Multiplier applied to the buffer argument from the cli to determine the maximum number
 /// of simultaneous request to the `chunk/ endpoint`.
 pub const CHUNKS_BUFFER_FACTOR: usize = 20;
@@ -15,6 +30,27 @@ pub const CHUNKS_RETRY_SLEEP: u64 = 1;
 /// Number of seconds to wait between retying to post a failed chunk.
 pub const RETRY_SLEEP: u64 = 10;
 
+/// Number of times to retry a withdraw failing with a retryable error.
+pub const WITHDRAW_RETRIES: u16 = 3;
+
+/// Number of seconds to wait before retrying a failed withdraw.
+pub const WITHDRAW_RETRY_SLEEP: u64 = 1;
+
+/// Number of times to check the balance for a funding to be credited before giving up.
+pub const FUND_CREDIT_RETRIES: u16 = 30;
+
+/// Number of seconds to wait between checks of the balance for a funding to be credited.
+pub const FUND_CREDIT_RETRY_SLEEP: u64 = 10;
+
+/// Number of bytes priced to derive the per-byte price of the node.
+pub const PRICE_SAMPLE_BYTES: u64 = 1024 * 1024;
+
+/// Number of seconds the per-byte price of the node is cached for.
+pub const PRICE_CACHE_TTL: u64 = 60;
+
+/// Number of items checked at the same time by `Bundlr::which_exist`.
+pub const EXISTENCE_CHECK_CONCURRENCY: usize = 16;
+
 /// Number of confirmations needed to consider a transaction funded
 pub const CONFIRMATIONS_NEEDED: u64 = 5;
 
@@ -24,3 +60,14 @@ pub const LIST_AS_BUFFER: &[u8] = "list".as_bytes();
 pub const BLOB_AS_BUFFER: &[u8] = "blob".as_bytes();
 pub const DATAITEM_AS_BUFFER: &[u8] = "dataitem".as_bytes();
 pub const ONE_AS_BUFFER: &[u8] = "1".as_bytes();
+
+/// Prefix of the bundle files written by `bundle::write_bundle_compressed`
+pub const COMPRESSED_BUNDLE_MAGIC: &[u8; 8] = b"ANS104GZ";
+
+/// Maximum size in bytes the compressed section of a bundle may decompress to, as it is held in
+/// memory
+pub const MAX_DECOMPRESSED_SECTION_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Length in bytes of the RSA-PSS signature of an Arweave L1 transaction, e.g. one posting a
+/// bundle
+pub const L1_SIGNATURE_LENGTH: usize = 512;
@@ -1,8 +1,13 @@
-use arweave_rs::{crypto::base64::Base64, Arweave as ArweaveSdk};
+use arweave_rs::{
+    crypto::base64::Base64, types::TxStatus as ArweaveTxStatus, Arweave as ArweaveSdk,
+};
 use bytes::Bytes;
+use data_encoding::BASE64URL_NOPAD;
 use num::ToPrimitive;
 use reqwest::{StatusCode, Url};
-use std::{ops::Mul, path::PathBuf, str::FromStr};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{fmt, ops::Mul, path::PathBuf, str::FromStr};
 
 use crate::{
     error::{BuilderError, BundlrError},
@@ -16,6 +21,43 @@ const ARWEAVE_TICKER: &str = "AR";
 const ARWEAVE_BASE_UNIT: &str = "winston";
 const ARWEAVE_BASE_URL: &str = "https://arweave.net/";
 
+#[derive(Deserialize)]
+struct NetworkInfo {
+    height: u128,
+}
+
+/// Id of an Arweave L1 transaction, e.g. a funding transfer sent with [`Arweave::send_l1_tx`].
+/// Unlike the id of an ANS-104 data item, it can be looked up on the Arweave network itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArweaveTxId(String);
+
+impl ArweaveTxId {
+    /// Id of the transaction with `signature`, i.e. the base64url SHA-256 of the signature
+    pub fn from_signature(signature: &[u8]) -> Self {
+        ArweaveTxId(BASE64URL_NOPAD.encode(&Sha256::digest(signature)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ArweaveTxId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Id computed from `signature`, failing with `BundlrError::InvalidHeaders` if it isn't the
+/// `id` the SDK gave the transaction
+fn checked_tx_id(id: &Base64, signature: &Base64) -> Result<ArweaveTxId, BundlrError> {
+    let tx_id = ArweaveTxId::from_signature(&signature.0);
+    if id.to_string() != tx_id.as_str() {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    Ok(tx_id)
+}
+
 #[allow(unused)]
 pub struct Arweave {
     sdk: ArweaveSdk,
@@ -27,6 +69,7 @@ pub struct Arweave {
     ticker: String,
     min_confirm: i16,
     client: reqwest::Client,
+    url: Url,
 }
 
 #[derive(Default)]
@@ -56,14 +99,13 @@ impl ArweaveBuilder {
             .unwrap_or_else(|| Url::from_str(ARWEAVE_BASE_URL).unwrap());
 
         let sdk = match &self.keypair_path {
-            // With signer
-            Some(keypair_path) => arweave_rs::ArweaveBuilder::new()
-                .base_url(base_url)
-                .keypair_path(keypair_path.clone())
-                .build()?,
+            // With signer, built so that the SDK sends its requests to `base_url` as well
+            Some(keypair_path) => {
+                ArweaveSdk::from_keypair_path(keypair_path.clone(), base_url.clone())?
+            }
             // Without signer
             None => arweave_rs::ArweaveBuilder::new()
-                .base_url(base_url)
+                .base_url(base_url.clone())
                 .build()?,
         };
 
@@ -82,6 +124,7 @@ impl ArweaveBuilder {
             ticker: ARWEAVE_TICKER.to_string(),
             min_confirm: 5,
             client: reqwest::Client::new(),
+            url: base_url,
         })
     }
 }
@@ -92,6 +135,10 @@ impl Currency for Arweave {
         ARWEAVE_BASE_UNIT.to_string()
     }
 
+    fn ticker(&self) -> String {
+        self.ticker.clone()
+    }
+
     fn get_type(&self) -> CurrencyType {
         self.name
     }
@@ -133,32 +180,37 @@ impl Currency for Arweave {
         &self,
         tx_id: String,
     ) -> Result<(StatusCode, Option<TxStatus>), BundlrError> {
+        let url = self
+            .url
+            .join(&format!(
+                "tx/{}/status",
+                Base64::from_str(&tx_id).map_err(|err| BundlrError::ParseError(err.to_string()))?
+            ))
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
         let res = self
-            .sdk
-            .get_tx_status(
-                Base64::from_str(&tx_id).map_err(|err| BundlrError::ParseError(err.to_string()))?,
-            )
-            .await;
-
-        if let Ok((status, tx_status)) = res {
-            if status == StatusCode::OK {
-                match tx_status {
-                    Some(tx_status) => Ok((
-                        status,
-                        Some(TxStatus {
-                            confirmations: tx_status.number_of_confirmations,
-                            height: tx_status.block_height,
-                            block_hash: tx_status.block_indep_hash.to_string(),
-                        }),
-                    )),
-                    None => Ok((status, None)),
-                }
-            } else {
-                //Tx is pending
-                Ok((status, None))
-            }
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| BundlrError::RequestError(err.to_string()))?;
+
+        let status = res.status();
+        if status == StatusCode::OK {
+            let tx_status = res
+                .json::<ArweaveTxStatus>()
+                .await
+                .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+            Ok((
+                status,
+                Some(TxStatus {
+                    confirmations: tx_status.number_of_confirmations,
+                    height: tx_status.block_height,
+                    block_hash: tx_status.block_indep_hash.to_string(),
+                }),
+            ))
         } else {
-            Err(BundlrError::TxStatusNotConfirmed)
+            //Tx is pending
+            Ok((status, None))
         }
     }
 
@@ -215,8 +267,20 @@ impl Currency for Arweave {
         todo!();
     }
 
-    async fn get_current_height(&self) -> u128 {
-        todo!();
+    async fn get_current_height(&self) -> Result<u128, BundlrError> {
+        let url = self
+            .url
+            .join("info")
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+        self.client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| BundlrError::RequestError(err.to_string()))?
+            .json::<NetworkInfo>()
+            .await
+            .map(|info| info.height)
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))
     }
 
     async fn get_fee(&self, _amount: u64, to: &str, multiplier: f64) -> Result<u64, BundlrError> {
@@ -275,6 +339,18 @@ impl Currency for Arweave {
     }
 
     async fn send_tx(&self, data: Tx) -> Result<TxResponse, BundlrError> {
+        let tx_id = self.send_l1_tx(data).await?;
+        Ok(TxResponse {
+            tx_id: tx_id.to_string(),
+        })
+    }
+}
+
+impl Arweave {
+    /// Same as [`Currency::send_tx`], but returns the id of the L1 transaction as an
+    /// [`ArweaveTxId`]. The id is computed from the transaction signature and checked against
+    /// the one given by the SDK before the transaction is posted.
+    pub async fn send_l1_tx(&self, data: Tx) -> Result<ArweaveTxId, BundlrError> {
         let tx = self
             .sdk
             .create_transaction(
@@ -293,21 +369,36 @@ impl Currency for Arweave {
             .sdk
             .sign_transaction(tx)
             .map_err(BundlrError::ArweaveSdkError)?;
-        let (tx_id, _r) = self
-            .sdk
+        let tx_id = checked_tx_id(&signed_tx.id, &signed_tx.signature)?;
+        self.sdk
             .post_transaction(&signed_tx)
             .await
             .map_err(BundlrError::ArweaveSdkError)?;
 
-        Ok(TxResponse { tx_id })
+        Ok(tx_id)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{path::PathBuf, str::FromStr};
+    use std::{path::PathBuf, str::FromStr, sync::Mutex, time::Duration};
+
+    use data_encoding::BASE64URL_NOPAD;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use reqwest::Url;
 
-    use crate::currency::{arweave::ArweaveBuilder, Currency};
+    use arweave_rs::crypto::base64::Base64;
+    use sha2::{Digest, Sha256};
+
+    use super::{checked_tx_id, ArweaveTxId};
+    use crate::{
+        currency::{arweave::ArweaveBuilder, Currency},
+        error::BundlrError,
+        transaction::Tx,
+    };
 
     #[test]
     fn should_sign_and_verify() {
@@ -328,6 +419,135 @@ mod tests {
         assert!(c.verify(&pub_key, &msg, &sig).is_ok());
     }
 
+    #[test]
+    fn should_name_currency() {
+        let wallet = PathBuf::from_str("res/test_wallet.json").expect("Could not load path");
+        let c = ArweaveBuilder::new()
+            .keypair_path(wallet)
+            .build()
+            .expect("Could not build arweave");
+
+        assert_eq!(c.ticker(), "AR");
+        assert_eq!(c.network_name(), "arweave");
+    }
+
+    #[tokio::test]
+    async fn should_get_current_height() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/info");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"network\": \"arweave.N.1\", \"height\": 1268541 }");
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let c = ArweaveBuilder::new()
+            .base_url(url)
+            .build()
+            .expect("Could not build arweave");
+        let height = c.get_current_height().await.unwrap();
+
+        mock.assert();
+        assert_eq!(height, 1268541);
+    }
+
+    #[tokio::test]
+    async fn should_count_confirmations_remaining() {
+        let tx_id = "rhyiwSVFgGzVRMu7bNG1tsShTUi9xRHvESoG0tE5m9c";
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path(format!("/tx/{}/status", tx_id));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    serde_json::json!({
+                        "block_height": 1268541,
+                        "block_indep_hash": "sDHKuCSTU3_bmD1nPQWUiJzaDF2oQmEcPl3pNRdbJfYOKN5hTzr2wQvVKW3DVgQg",
+                        "number_of_confirmations": 2,
+                    })
+                    .to_string(),
+                );
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let c = ArweaveBuilder::new()
+            .base_url(url)
+            .build()
+            .expect("Could not build arweave");
+
+        assert_eq!(c.min_confirmations(), 5);
+        assert_eq!(c.confirmations_remaining(tx_id).await.unwrap(), 3);
+        assert_eq!(
+            c.estimated_confirmation_time(tx_id).await.unwrap(),
+            Duration::from_secs(360)
+        );
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn should_check_l1_tx_id() {
+        let signature = Base64(vec![7u8; 512]);
+        let id = Base64(Sha256::digest(&signature.0).to_vec());
+
+        let tx_id = checked_tx_id(&id, &signature).unwrap();
+        assert_eq!(tx_id.as_str(), id.to_string());
+        assert_eq!(tx_id, ArweaveTxId::from_signature(&signature.0));
+        assert!(matches!(
+            checked_tx_id(&Base64(vec![0u8; 32]), &signature),
+            Err(BundlrError::InvalidHeaders)
+        ));
+    }
+
+    static POSTED_TX: Mutex<Option<serde_json::Value>> = Mutex::new(None);
+
+    #[tokio::test]
+    async fn should_return_id_of_posted_l1_tx() {
+        let server = MockServer::start();
+        let anchor_mock = server.mock(|when, then| {
+            when.method(GET).path("/tx_anchor");
+            then.status(200).body(BASE64URL_NOPAD.encode(&[1u8; 48]));
+        });
+        let tx_mock = server.mock(|when, then| {
+            when.method(POST).path("/tx").matches(|req| {
+                let body = req.body.as_deref().unwrap_or_default();
+                *POSTED_TX.lock().unwrap() = serde_json::from_slice(body).ok();
+                true
+            });
+            then.status(200);
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let wallet = PathBuf::from_str("res/test_wallet.json").expect("Could not load path");
+        let c = ArweaveBuilder::new()
+            .base_url(url)
+            .keypair_path(wallet)
+            .build()
+            .expect("Could not build arweave");
+        let tx_id = c
+            .send_l1_tx(Tx {
+                id: String::new(),
+                from: String::new(),
+                to: BASE64URL_NOPAD.encode(&[2u8; 32]),
+                amount: 1000,
+                fee: 10,
+                block_height: 0,
+                pending: false,
+                confirmed: false,
+            })
+            .await
+            .unwrap();
+
+        anchor_mock.assert();
+        tx_mock.assert();
+        let posted = POSTED_TX.lock().unwrap().take().unwrap();
+        assert_eq!(posted["id"].as_str(), Some(tx_id.as_str()));
+        let signature = BASE64URL_NOPAD
+            .decode(posted["signature"].as_str().unwrap().as_bytes())
+            .unwrap();
+        assert_eq!(tx_id, ArweaveTxId::from_signature(&signature));
+    }
+
     #[tokio::test]
     async fn should_get_fee_correctly() {}
 }
@@ -1,5 +1,9 @@
+use std::ops::Mul;
+
 use bytes::Bytes;
+use num_traits::ToPrimitive;
 use reqwest::{StatusCode, Url};
+use serde::Deserialize;
 
 use crate::{
     error::{BuilderError, BundlrError},
@@ -12,6 +16,12 @@ use super::{Currency, CurrencyType, TxResponse};
 const ETHEREUM_TICKER: &str = "ETH";
 const ETHEREUM_BASE_UNIT: &str = "wei";
 const ETHEREUM_BASE_URL: &str = "https://etherscan.io/";
+const ETHEREUM_TRANSFER_GAS: u64 = 21000;
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: String,
+}
 
 #[allow(unused)]
 pub struct Ethereum {
@@ -80,6 +90,43 @@ impl EthereumBuilder {
     }
 }
 
+impl Ethereum {
+    async fn rpc_call(&self, method: &str) -> Result<String, BundlrError> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": [],
+            "id": 1,
+        });
+        self.client
+            .post(self.url.clone())
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| BundlrError::RequestError(err.to_string()))?
+            .json::<RpcResponse>()
+            .await
+            .map(|res| res.result)
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))
+    }
+
+    async fn get_gas_price(&self) -> Result<u64, BundlrError> {
+        let res = self.rpc_call("eth_gasPrice").await?;
+        u64::from_str_radix(res.trim_start_matches("0x"), 16)
+            .map_err(|err| BundlrError::TypeParseError(err.to_string()))
+    }
+
+    fn transfer_fee(gas_price: u64, multiplier: f64) -> Result<u64, BundlrError> {
+        let fee = (gas_price as f64).mul(ETHEREUM_TRANSFER_GAS as f64);
+        match multiplier.mul(fee).ceil().to_u64() {
+            Some(fee) => Ok(fee),
+            None => Err(BundlrError::TypeParseError(
+                "Could not convert fee to u64".to_string(),
+            )),
+        }
+    }
+}
+
 #[allow(unused)]
 #[async_trait::async_trait]
 impl Currency for Ethereum {
@@ -87,6 +134,10 @@ impl Currency for Ethereum {
         ETHEREUM_BASE_UNIT.to_string()
     }
 
+    fn ticker(&self) -> String {
+        self.ticker.clone()
+    }
+
     fn get_type(&self) -> CurrencyType {
         self.name
     }
@@ -154,12 +205,30 @@ impl Currency for Ethereum {
         todo!();
     }
 
-    async fn get_current_height(&self) -> u128 {
-        todo!();
+    async fn get_current_height(&self) -> Result<u128, BundlrError> {
+        let res = self.rpc_call("eth_blockNumber").await?;
+        u128::from_str_radix(res.trim_start_matches("0x"), 16)
+            .map_err(|err| BundlrError::TypeParseError(err.to_string()))
     }
 
     async fn get_fee(&self, _amount: u64, to: &str, multiplier: f64) -> Result<u64, BundlrError> {
-        todo!();
+        let gas_price = self.get_gas_price().await?;
+        Self::transfer_fee(gas_price, multiplier)
+    }
+
+    async fn get_fees(
+        &self,
+        requests: &[(u64, String)],
+        multiplier: f64,
+    ) -> Result<Vec<u64>, BundlrError> {
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+        let gas_price = self.get_gas_price().await?;
+        requests
+            .iter()
+            .map(|_| Self::transfer_fee(gas_price, multiplier))
+            .collect()
     }
 
     async fn create_tx(&self, amount: u64, to: &str, fee: u64) -> Result<Tx, BundlrError> {
@@ -170,3 +239,61 @@ impl Currency for Ethereum {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{Method::POST, MockServer};
+    use reqwest::Url;
+
+    use crate::currency::Currency;
+
+    use super::EthereumBuilder;
+
+    #[tokio::test]
+    async fn should_get_fees_with_one_gas_price_call() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("eth_gasPrice");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"jsonrpc\": \"2.0\", \"id\": 1, \"result\": \"0x3b9aca00\" }");
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let ethereum = EthereumBuilder::new().base_url(url).build().unwrap();
+        let requests = vec![
+            (1, "0x0000000000000000000000000000000000000001".to_string()),
+            (2, "0x0000000000000000000000000000000000000002".to_string()),
+            (3, "0x0000000000000000000000000000000000000003".to_string()),
+        ];
+        let fees = ethereum.get_fees(&requests, 1.5).await.unwrap();
+
+        mock.assert_hits(1);
+        assert_eq!(fees, vec![31_500_000_000_000; 3]);
+    }
+
+    #[tokio::test]
+    async fn should_get_current_height() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("eth_blockNumber");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"jsonrpc\": \"2.0\", \"id\": 1, \"result\": \"0x10d4f\" }");
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let ethereum = EthereumBuilder::new().base_url(url).build().unwrap();
+        let height = ethereum.get_current_height().await.unwrap();
+
+        mock.assert();
+        assert_eq!(height, 68943);
+    }
+
+    #[test]
+    fn should_name_currency() {
+        let ethereum = EthereumBuilder::new().build().unwrap();
+        assert_eq!(ethereum.ticker(), "ETH");
+        assert_eq!(ethereum.network_name(), "ethereum");
+    }
+}
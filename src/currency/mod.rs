@@ -9,16 +9,20 @@ pub mod ethereum;
 use core::fmt;
 
 use bytes::Bytes;
+use num::BigUint;
 use num_derive::FromPrimitive;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use std::time::Duration;
 
 #[cfg(feature = "build-binary")]
 use clap::ValueEnum;
 
 use crate::{
+    consts::CONFIRMATIONS_NEEDED,
     error::BundlrError,
+    index::SignerMap,
     transaction::{Tx, TxStatus},
     Signer,
 };
@@ -33,6 +37,68 @@ pub enum CurrencyType {
     Cosmos = 5,
 }
 
+impl CurrencyType {
+    /// Number of decimals between the base unit and the whole unit of the currency. ERC20 tokens
+    /// define their own, so they are unknown here and fail with `BundlrError::CurrencyError`:
+    /// use [`Currency::decimals`] of the token instead.
+    pub fn decimals(&self) -> Result<u32, BundlrError> {
+        match self {
+            CurrencyType::Arweave => Ok(12),
+            CurrencyType::Solana => Ok(9),
+            CurrencyType::Ethereum => Ok(18),
+            CurrencyType::Erc20 => Err(BundlrError::CurrencyError(
+                "Decimals of ERC20 tokens are defined by each token".to_owned(),
+            )),
+            CurrencyType::Cosmos => Ok(6),
+        }
+    }
+
+    /// Ticker symbol of the currency. ERC20 tokens define their own, so `ERC20` is only a
+    /// placeholder for them: prefer [`Currency::ticker`] when a currency instance is available.
+    pub fn ticker(&self) -> &'static str {
+        match self {
+            CurrencyType::Arweave => "AR",
+            CurrencyType::Solana => "SOL",
+            CurrencyType::Ethereum => "ETH",
+            CurrencyType::Erc20 => "ERC20",
+            CurrencyType::Cosmos => "ATOM",
+        }
+    }
+
+    /// Average time between two blocks of the currency network, used for rough confirmation
+    /// time estimates
+    pub fn block_time(&self) -> Duration {
+        match self {
+            CurrencyType::Arweave => Duration::from_secs(120),
+            CurrencyType::Solana => Duration::from_millis(400),
+            CurrencyType::Ethereum => Duration::from_secs(12),
+            CurrencyType::Erc20 => Duration::from_secs(12),
+            CurrencyType::Cosmos => Duration::from_secs(6),
+        }
+    }
+
+    /// Signature type used by the signers of the currency
+    pub fn signer_type(&self) -> SignerMap {
+        match self {
+            CurrencyType::Arweave => SignerMap::Arweave,
+            CurrencyType::Solana => SignerMap::Solana,
+            CurrencyType::Ethereum | CurrencyType::Erc20 => SignerMap::Ethereum,
+            CurrencyType::Cosmos => SignerMap::Cosmos,
+        }
+    }
+}
+
+/// Verifies that `signature` is the signature of `message` by `pub_key` for the given currency,
+/// without building the currency nor its signer.
+pub fn verify_message(
+    currency: CurrencyType,
+    pub_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), BundlrError> {
+    currency.signer_type().verify(pub_key, message, signature)
+}
+
 #[derive(Deserialize)]
 pub struct TxResponse {
     pub tx_id: String,
@@ -58,6 +124,111 @@ impl FromStr for CurrencyType {
     }
 }
 
+const KEYPAIR_LENGTH: usize = 64;
+
+/// Checks that `wallet` has the expected format for the given currency, without building the
+/// currency nor its signer.
+///
+/// Arweave wallets are expected to be JWK json strings, Solana and Cosmos wallets base58 encoded
+/// 64 byte keypairs, and Ethereum/ERC20 wallets base58 encoded 64 byte keypairs holding a valid
+/// secp256k1 secret key, as decoded by [`ethereum::EthereumBuilder::build`].
+pub fn validate_wallet(currency: CurrencyType, wallet: &str) -> Result<(), BundlrError> {
+    match currency {
+        CurrencyType::Arweave => validate_jwk(wallet),
+        CurrencyType::Solana => validate_base58_keypair(wallet, "Solana"),
+        CurrencyType::Ethereum | CurrencyType::Erc20 => validate_ethereum_keypair(wallet),
+        CurrencyType::Cosmos => validate_base58_keypair(wallet, "Cosmos"),
+    }
+}
+
+fn validate_jwk(wallet: &str) -> Result<(), BundlrError> {
+    let invalid = || {
+        BundlrError::InvalidKey(
+            "this doesn't look like an Arweave key, expected a RSA JWK json".to_owned(),
+        )
+    };
+    let jwk = serde_json::from_str::<serde_json::Value>(wallet).map_err(|_| invalid())?;
+    if jwk.get("kty").and_then(|kty| kty.as_str()) != Some("RSA") {
+        return Err(invalid());
+    }
+    for field in ["n", "e", "d"] {
+        if !jwk.get(field).map(|v| v.is_string()).unwrap_or(false) {
+            return Err(invalid());
+        }
+    }
+    Ok(())
+}
+
+fn validate_base58_keypair(wallet: &str, name: &str) -> Result<(), BundlrError> {
+    match bs58::decode(wallet).into_vec() {
+        Ok(key) if key.len() == KEYPAIR_LENGTH => Ok(()),
+        _ => Err(BundlrError::InvalidKey(format!(
+            "this doesn't look like a {} key, expected a base58 encoded {} byte keypair",
+            name, KEYPAIR_LENGTH
+        ))),
+    }
+}
+
+#[cfg(any(feature = "ethereum", feature = "erc20"))]
+fn validate_ethereum_keypair(wallet: &str) -> Result<(), BundlrError> {
+    crate::Secp256k1Signer::from_base58(wallet)
+        .map(|_| ())
+        .map_err(|_| {
+            BundlrError::InvalidKey(format!(
+                "this doesn't look like an Ethereum key, expected a base58 encoded {} byte keypair",
+                KEYPAIR_LENGTH
+            ))
+        })
+}
+
+#[cfg(not(any(feature = "ethereum", feature = "erc20")))]
+fn validate_ethereum_keypair(wallet: &str) -> Result<(), BundlrError> {
+    validate_base58_keypair(wallet, "Ethereum")
+}
+
+/// Formats an amount of base units in whole units, e.g. `1500000000` lamports as `"1.5"`
+pub fn format_amount(amount: &BigUint, decimals: u32) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// Parses an amount in whole units, e.g. `"1.5"`, into base units
+pub fn parse_amount(amount: &str, decimals: u32) -> Result<BigUint, BundlrError> {
+    let invalid = || BundlrError::ParseError(format!("Invalid amount {}", amount));
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    if fraction.len() > decimals as usize {
+        return Err(BundlrError::ParseError(format!(
+            "Amount {} has more than {} decimals",
+            amount, decimals
+        )));
+    }
+
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    BigUint::from_str(&digits).map_err(|_| invalid())
+}
+
 #[async_trait::async_trait]
 pub trait Currency {
     /// Gets the base unit name, such as "winston" for Arweave
@@ -66,6 +237,23 @@ pub trait Currency {
     /// Gets currency type
     fn get_type(&self) -> CurrencyType;
 
+    /// Gets the ticker symbol of the currency, such as "AR" for Arweave, see
+    /// [`CurrencyType::ticker`]
+    fn ticker(&self) -> String {
+        self.get_type().ticker().to_string()
+    }
+
+    /// Gets the name of the currency network, such as "arweave"
+    fn network_name(&self) -> String {
+        self.get_type().to_string()
+    }
+
+    /// Gets the number of decimals of the currency, see [`CurrencyType::decimals`]. ERC20 tokens
+    /// override it with the decimals of their token.
+    fn decimals(&self) -> Result<u32, BundlrError> {
+        self.get_type().decimals()
+    }
+
     /// Returns if the currency needs fee for transacting
     fn needs_fee(&self) -> bool;
 
@@ -78,6 +266,31 @@ pub trait Currency {
         tx_id: String,
     ) -> Result<(StatusCode, Option<TxStatus>), BundlrError>;
 
+    /// Gets the number of confirmations after which a transaction is considered final
+    fn min_confirmations(&self) -> u64 {
+        CONFIRMATIONS_NEEDED
+    }
+
+    /// Gets the number of confirmations the transaction still needs to be considered final, all
+    /// of them while it is pending
+    async fn confirmations_remaining(&self, tx_id: &str) -> Result<u64, BundlrError> {
+        let (_, tx_status) = self.get_tx_status(tx_id.to_owned()).await?;
+        let confirmations = tx_status.map(|status| status.confirmations).unwrap_or(0);
+        Ok(self.min_confirmations().saturating_sub(confirmations))
+    }
+
+    /// Gets the average time between two blocks, see [`CurrencyType::block_time`]
+    fn block_time(&self) -> Duration {
+        self.get_type().block_time()
+    }
+
+    /// Rough estimate of the time until the transaction is final, from its remaining
+    /// confirmations and the average block time
+    async fn estimated_confirmation_time(&self, tx_id: &str) -> Result<Duration, BundlrError> {
+        let remaining = self.confirmations_remaining(tx_id).await?;
+        Ok(self.block_time() * u32::try_from(remaining).unwrap_or(u32::MAX))
+    }
+
     /// Gets public key
     fn get_pub_key(&self) -> Result<Bytes, BundlrError>;
 
@@ -99,15 +312,147 @@ pub trait Currency {
     /// Get price of currency in USD
     async fn price(&self) -> String;
 
-    /// Get given currency network's block height
-    async fn get_current_height(&self) -> u128;
+    /// Get the height of the latest block confirmed by the given currency network
+    async fn get_current_height(&self) -> Result<u128, BundlrError>;
 
     /// Get fee for transaction
     async fn get_fee(&self, amount: u64, to: &str, multiplier: f64) -> Result<u64, BundlrError>;
 
+    /// Get fees for a batch of `(amount, to)` transactions. Currencies that can price several
+    /// transactions with a single request should override it.
+    async fn get_fees(
+        &self,
+        requests: &[(u64, String)],
+        multiplier: f64,
+    ) -> Result<Vec<u64>, BundlrError> {
+        let mut fees = Vec::with_capacity(requests.len());
+        for (amount, to) in requests {
+            fees.push(self.get_fee(*amount, to, multiplier).await?);
+        }
+        Ok(fees)
+    }
+
     /// Creates a new transaction
     async fn create_tx(&self, amount: u64, to: &str, fee: u64) -> Result<Tx, BundlrError>;
 
     /// Send a signed transaction
     async fn send_tx(&self, data: Tx) -> Result<TxResponse, BundlrError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use num::BigUint;
+
+    use super::{format_amount, parse_amount, validate_wallet, verify_message, CurrencyType};
+    use crate::{error::BundlrError, test_support::TEST_KEYPAIR};
+
+    #[test]
+    fn should_validate_arweave_wallet() {
+        let jwk = std::fs::read_to_string("res/test_wallet.json").unwrap();
+        assert!(validate_wallet(CurrencyType::Arweave, &jwk).is_ok());
+        assert!(validate_wallet(CurrencyType::Arweave, TEST_KEYPAIR).is_err());
+        assert!(validate_wallet(CurrencyType::Arweave, r#"{"kty":"EC"}"#).is_err());
+    }
+
+    #[test]
+    fn should_validate_base58_wallets() {
+        assert!(validate_wallet(CurrencyType::Solana, TEST_KEYPAIR).is_ok());
+        assert!(validate_wallet(CurrencyType::Cosmos, TEST_KEYPAIR).is_ok());
+        assert!(validate_wallet(CurrencyType::Ethereum, TEST_KEYPAIR).is_ok());
+        assert!(validate_wallet(CurrencyType::Solana, "kNykCXNxgePDjFbD").is_err());
+        assert!(validate_wallet(CurrencyType::Solana, "0OIl").is_err());
+    }
+
+    #[test]
+    fn should_reject_wallets_the_ethereum_builder_rejects() {
+        // Hex secret keys aren't decoded by `EthereumBuilder::build`
+        let hex_key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+        assert!(validate_wallet(CurrencyType::Ethereum, hex_key).is_err());
+        assert!(validate_wallet(CurrencyType::Erc20, &hex_key[2..]).is_err());
+        // 64 bytes, but not a valid secp256k1 secret key
+        let zero_keypair = bs58::encode([0u8; 64]).into_string();
+        assert!(validate_wallet(CurrencyType::Ethereum, &zero_keypair).is_err());
+        assert!(validate_wallet(CurrencyType::Solana, &zero_keypair).is_ok());
+    }
+
+    #[test]
+    fn should_have_decimals() {
+        assert_eq!(CurrencyType::Arweave.decimals().unwrap(), 12);
+        assert_eq!(CurrencyType::Solana.decimals().unwrap(), 9);
+        assert_eq!(CurrencyType::Ethereum.decimals().unwrap(), 18);
+        assert!(matches!(
+            CurrencyType::Erc20.decimals(),
+            Err(BundlrError::CurrencyError(_))
+        ));
+        assert_eq!(CurrencyType::Cosmos.decimals().unwrap(), 6);
+    }
+
+    #[test]
+    fn should_have_tickers() {
+        assert_eq!(CurrencyType::Arweave.ticker(), "AR");
+        assert_eq!(CurrencyType::Solana.ticker(), "SOL");
+        assert_eq!(CurrencyType::Ethereum.ticker(), "ETH");
+        assert_eq!(CurrencyType::Cosmos.ticker(), "ATOM");
+    }
+
+    #[test]
+    fn should_format_and_parse_amounts() {
+        let decimals = CurrencyType::Solana.decimals().unwrap();
+        let cases = [
+            (1_500_000_000u64, "1.5"),
+            (1, "0.000000001"),
+            (0, "0"),
+            (42_000_000_000, "42"),
+        ];
+        for (base_units, formatted) in cases {
+            let base_units = BigUint::from(base_units);
+            assert_eq!(format_amount(&base_units, decimals), formatted);
+            assert_eq!(parse_amount(formatted, decimals).unwrap(), base_units);
+        }
+
+        assert_eq!(
+            parse_amount(".25", decimals).unwrap(),
+            BigUint::from(250_000_000u64)
+        );
+        assert_eq!(format_amount(&BigUint::from(7u64), 0), "7");
+        assert!(parse_amount("0.0000000001", decimals).is_err());
+        assert!(parse_amount("1.5.0", decimals).is_err());
+        assert!(parse_amount("-1", decimals).is_err());
+        assert!(parse_amount(".", decimals).is_err());
+    }
+
+    #[cfg(feature = "arweave")]
+    #[test]
+    fn should_verify_arweave_message() {
+        use crate::{ArweaveSigner, Signer};
+        use std::path::PathBuf;
+
+        let msg = b"Hello, Bundlr!";
+        let signer =
+            ArweaveSigner::from_keypair_path(PathBuf::from("res/test_wallet.json")).unwrap();
+        let sig = signer.sign(Bytes::from_static(msg)).unwrap();
+        let pub_key = signer.pub_key();
+
+        assert!(verify_message(CurrencyType::Arweave, &pub_key, msg, &sig).is_ok());
+        assert!(verify_message(CurrencyType::Arweave, &pub_key, b"Hello, Irys!", &sig).is_err());
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn should_verify_ethereum_message() {
+        use crate::{Secp256k1Signer, Signer};
+        use secp256k1::SecretKey;
+
+        let msg = b"Hello, Bundlr!";
+        let secret_key = SecretKey::from_slice(b"00000000000000000000000000000000").unwrap();
+        let signer = Secp256k1Signer::new(secret_key);
+        let sig = signer.sign(Bytes::from_static(msg)).unwrap();
+        let pub_key = signer.pub_key();
+
+        assert!(verify_message(CurrencyType::Ethereum, &pub_key, msg, &sig).is_ok());
+        assert!(verify_message(CurrencyType::Erc20, &pub_key, msg, &sig).is_ok());
+        assert!(verify_message(CurrencyType::Ethereum, &pub_key, b"Hello, Irys!", &sig).is_err());
+        assert!(verify_message(CurrencyType::Solana, &pub_key, msg, &sig).is_err());
+    }
+}
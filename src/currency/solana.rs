@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use reqwest::{StatusCode, Url};
+use serde::Deserialize;
 
 use crate::{
     error::{BuilderError, BundlrError},
@@ -13,6 +14,11 @@ const SOLANA_TICKER: &str = "SOL";
 const SOLANA_BASE_UNIT: &str = "lamport";
 const SOLANA_BASE_URL: &str = "https://explorer.solana.com/";
 
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: T,
+}
+
 #[allow(unused)]
 pub struct Solana {
     signer: Option<Ed25519Signer>,
@@ -87,6 +93,10 @@ impl Currency for Solana {
         SOLANA_BASE_UNIT.to_string()
     }
 
+    fn ticker(&self) -> String {
+        self.ticker.clone()
+    }
+
     fn get_type(&self) -> CurrencyType {
         self.name
     }
@@ -134,7 +144,7 @@ impl Currency for Solana {
     }
 
     fn wallet_address(&self) -> Result<String, BundlrError> {
-        todo!();
+        Ok(bs58::encode(self.get_pub_key()?).into_string())
     }
 
     fn get_signer(&self) -> Result<&dyn Signer, BundlrError> {
@@ -154,8 +164,23 @@ impl Currency for Solana {
         todo!();
     }
 
-    async fn get_current_height(&self) -> u128 {
-        todo!();
+    async fn get_current_height(&self) -> Result<u128, BundlrError> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "getSlot",
+            "params": [{ "commitment": "confirmed" }],
+            "id": 1,
+        });
+        self.client
+            .post(self.url.clone())
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| BundlrError::RequestError(err.to_string()))?
+            .json::<RpcResponse<u64>>()
+            .await
+            .map(|res| res.result.into())
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))
     }
 
     async fn get_fee(&self, _amount: u64, to: &str, multiplier: f64) -> Result<u64, BundlrError> {
@@ -170,3 +195,38 @@ impl Currency for Solana {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{Method::POST, MockServer};
+    use reqwest::Url;
+
+    use crate::currency::Currency;
+
+    use super::SolanaBuilder;
+
+    #[tokio::test]
+    async fn should_get_current_height() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("getSlot");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"jsonrpc\": \"2.0\", \"id\": 1, \"result\": 1234 }");
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let solana = SolanaBuilder::new().base_url(url).build().unwrap();
+        let height = solana.get_current_height().await.unwrap();
+
+        mock.assert();
+        assert_eq!(height, 1234);
+    }
+
+    #[test]
+    fn should_name_currency() {
+        let solana = SolanaBuilder::new().build().unwrap();
+        assert_eq!(solana.ticker(), "SOL");
+        assert_eq!(solana.network_name(), "solana");
+    }
+}
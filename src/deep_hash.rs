@@ -2,13 +2,14 @@ use std::pin::Pin;
 
 use async_recursion::async_recursion;
 use bytes::Bytes;
+#[cfg(not(feature = "ring-sha"))]
 use sha2::{Digest, Sha384};
 
 use crate::{
     consts::{BLOB_AS_BUFFER, LIST_AS_BUFFER},
     error::BundlrError,
 };
-use futures::{Stream, TryStream, TryStreamExt};
+use futures::{future::try_join_all, Stream, TryStream, TryStreamExt};
 
 pub enum DeepHashChunk<'a> {
     Chunk(Bytes),
@@ -18,15 +19,92 @@ pub enum DeepHashChunk<'a> {
 
 trait Foo: Stream<Item = anyhow::Result<Bytes>> + TryStream {}
 
+/// Version of the deep hash algorithm, i.e. the markers tagging blobs and lists along with the
+/// hash function. Only the ANS-104 v1 algorithm exists for now, but spec revisions can be added
+/// as new variants and used side by side with [`deep_hash_with_version`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeepHashVersion {
+    /// `blob` and `list` markers, hashed with SHA-384
+    #[default]
+    V1,
+}
+
+impl DeepHashVersion {
+    /// Marker prefixing the length of a blob
+    pub fn blob_marker(&self) -> &'static [u8] {
+        match self {
+            DeepHashVersion::V1 => BLOB_AS_BUFFER,
+        }
+    }
+
+    /// Marker prefixing the length of a list
+    pub fn list_marker(&self) -> &'static [u8] {
+        match self {
+            DeepHashVersion::V1 => LIST_AS_BUFFER,
+        }
+    }
+
+    /// Hashes `b` with the hash function of this version
+    pub fn hash(&self, b: &[u8]) -> Bytes {
+        match self {
+            DeepHashVersion::V1 => sha384hash(b),
+        }
+    }
+
+    fn hasher(&self) -> Sha384Hasher {
+        match self {
+            DeepHashVersion::V1 => Sha384Hasher::new(),
+        }
+    }
+}
+
+/// Incremental SHA-384 used by the deep hash. Backed by `sha2` by default, or by `ring` with
+/// the `ring-sha` feature, whose assembly implementations use the SHA extensions of the CPU
+/// where available.
+#[cfg(not(feature = "ring-sha"))]
+pub(crate) struct Sha384Hasher(Sha384);
+
+#[cfg(feature = "ring-sha")]
+pub(crate) struct Sha384Hasher(ring::digest::Context);
+
+impl Sha384Hasher {
+    pub(crate) fn new() -> Self {
+        #[cfg(not(feature = "ring-sha"))]
+        return Sha384Hasher(Sha384::new());
+        #[cfg(feature = "ring-sha")]
+        return Sha384Hasher(ring::digest::Context::new(&ring::digest::SHA384));
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    pub(crate) fn finalize(self) -> [u8; 48] {
+        #[cfg(not(feature = "ring-sha"))]
+        return self.0.finalize().into();
+        #[cfg(feature = "ring-sha")]
+        return self.0.finish().as_ref().try_into().unwrap(); // Unwrap ok, SHA-384 digests are 48 bytes
+    }
+}
+
 pub async fn deep_hash(chunk: DeepHashChunk<'_>) -> Result<Bytes, BundlrError> {
+    deep_hash_with_version(chunk, DeepHashVersion::V1).await
+}
+
+/// Same as [`deep_hash`], with the given version of the algorithm
+pub async fn deep_hash_with_version(
+    chunk: DeepHashChunk<'_>,
+    version: DeepHashVersion,
+) -> Result<Bytes, BundlrError> {
     match chunk {
         DeepHashChunk::Chunk(b) => {
-            let tag = [BLOB_AS_BUFFER, b.len().to_string().as_bytes()].concat();
-            let c = [sha384hash(tag.into()), sha384hash(b)].concat();
-            Ok(Bytes::copy_from_slice(&sha384hash(c.into())))
+            let tag = [version.blob_marker(), b.len().to_string().as_bytes()].concat();
+            let c = [version.hash(&tag), version.hash(&b)].concat();
+            Ok(version.hash(&c))
         }
         DeepHashChunk::Stream(s) => {
-            let mut hasher = Sha384::new();
+            let mut hasher = version.hasher();
             let mut length = 0;
             while let Some(chunk) = s
                 .as_mut()
@@ -38,28 +116,58 @@ pub async fn deep_hash(chunk: DeepHashChunk<'_>) -> Result<Bytes, BundlrError> {
                 hasher.update(&chunk);
             }
 
-            let tag = [BLOB_AS_BUFFER, length.to_string().as_bytes()].concat();
+            let tag = [version.blob_marker(), length.to_string().as_bytes()].concat();
 
             let tagged_hash = [
-                sha384hash(tag.into()),
+                version.hash(&tag),
                 Bytes::copy_from_slice(&hasher.finalize()),
             ]
             .concat();
 
-            Ok(sha384hash(tagged_hash.into()))
+            Ok(version.hash(&tagged_hash))
         }
         DeepHashChunk::Chunks(mut chunks) => {
             // Be careful of truncation
             let len = chunks.len() as f64;
-            let tag = [LIST_AS_BUFFER, len.to_string().as_bytes()].concat();
+            let tag = [version.list_marker(), len.to_string().as_bytes()].concat();
 
-            let acc = sha384hash(tag.into());
+            let acc = version.hash(&tag);
 
-            deep_hash_chunks(&mut chunks, acc).await
+            hash_chunks_concurrent(&mut chunks, acc, version).await
         }
     }
 }
 
+/// Hashes every chunk of a list concurrently, then folds the resulting hashes in order.
+///
+/// Each chunk of a list is an independent subtree, so only the final accumulation needs to be
+/// sequential. Produces the same result as [`deep_hash_chunks`].
+pub async fn deep_hash_chunks_concurrent(
+    chunks: &mut Vec<DeepHashChunk<'_>>,
+    acc: Bytes,
+) -> Result<Bytes, BundlrError> {
+    hash_chunks_concurrent(chunks, acc, DeepHashVersion::V1).await
+}
+
+#[async_recursion(?Send)]
+async fn hash_chunks_concurrent(
+    chunks: &mut Vec<DeepHashChunk<'_>>,
+    acc: Bytes,
+    version: DeepHashVersion,
+) -> Result<Bytes, BundlrError> {
+    let hashes = try_join_all(
+        chunks
+            .drain(..)
+            .map(|chunk| deep_hash_with_version(chunk, version)),
+    )
+    .await?;
+
+    Ok(hashes.into_iter().fold(acc, |acc, hash| {
+        let hash_pair = [acc, hash].concat();
+        version.hash(&hash_pair)
+    }))
+}
+
 #[async_recursion(?Send)]
 pub async fn deep_hash_chunks(
     chunks: &mut Vec<DeepHashChunk<'_>>,
@@ -72,12 +180,86 @@ pub async fn deep_hash_chunks(
     let acc = Bytes::copy_from_slice(&acc);
 
     let hash_pair = [acc, deep_hash(chunks.remove(0)).await?].concat();
-    let new_acc = sha384hash(hash_pair.into());
+    let new_acc = sha384hash(&hash_pair);
     deep_hash_chunks(chunks, new_acc).await
 }
 
-fn sha384hash(b: Bytes) -> Bytes {
-    let mut hasher = Sha384::new();
-    hasher.update(&b);
+fn sha384hash(b: &[u8]) -> Bytes {
+    let mut hasher = Sha384Hasher::new();
+    hasher.update(b);
     Bytes::copy_from_slice(&hasher.finalize())
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use data_encoding::HEXLOWER;
+
+    use crate::{
+        consts::{DATAITEM_AS_BUFFER, ONE_AS_BUFFER},
+        deep_hash_sync::{deep_hash_sync, deep_hash_sync_with_version},
+        tags::{AvroEncode, Tag},
+    };
+
+    use super::{deep_hash, deep_hash_with_version, DeepHashChunk, DeepHashVersion, Sha384Hasher};
+
+    fn large_tags_item() -> DeepHashChunk<'static> {
+        let tags = (0..128)
+            .map(|i| Tag::new(&format!("name-{}", i), &"value".repeat(512)))
+            .collect::<Vec<Tag>>();
+
+        DeepHashChunk::Chunks(vec![
+            DeepHashChunk::Chunk(DATAITEM_AS_BUFFER.into()),
+            DeepHashChunk::Chunk(ONE_AS_BUFFER.into()),
+            DeepHashChunk::Chunk(Bytes::from("2")),
+            DeepHashChunk::Chunk(Bytes::from(vec![1u8; 32])),
+            DeepHashChunk::Chunk(Bytes::new()),
+            DeepHashChunk::Chunk(Bytes::from(vec![2u8; 32])),
+            DeepHashChunk::Chunk(tags.encode().unwrap()),
+            DeepHashChunk::Chunk(Bytes::from(vec![3u8; 1024 * 1024])),
+        ])
+    }
+
+    #[test]
+    fn sha384_should_match_known_digests() {
+        // FIPS 180-2 test vectors, which every backend must reproduce
+        let mut hasher = Sha384Hasher::new();
+        hasher.update(b"abc");
+        assert_eq!(
+            HEXLOWER.encode(&hasher.finalize()),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+        );
+
+        let mut hasher = Sha384Hasher::new();
+        for _ in 0..1000 {
+            hasher.update(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            HEXLOWER.encode(&hasher.finalize()),
+            "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985"
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_hash_should_match_sequential() {
+        let concurrent = deep_hash(large_tags_item()).await.unwrap();
+        let sequential = deep_hash_sync(large_tags_item()).unwrap();
+
+        assert_eq!(concurrent, sequential);
+    }
+
+    #[tokio::test]
+    async fn default_version_should_match_deep_hash() {
+        assert_eq!(DeepHashVersion::default(), DeepHashVersion::V1);
+
+        let unversioned = deep_hash(large_tags_item()).await.unwrap();
+        let versioned = deep_hash_with_version(large_tags_item(), DeepHashVersion::default())
+            .await
+            .unwrap();
+        let versioned_sync =
+            deep_hash_sync_with_version(large_tags_item(), DeepHashVersion::default()).unwrap();
+
+        assert_eq!(unversioned, versioned);
+        assert_eq!(unversioned, versioned_sync);
+    }
+}
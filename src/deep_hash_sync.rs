@@ -1,9 +1,8 @@
 use bytes::Bytes;
-use sha2::{Digest, Sha384};
 
 use crate::{
     consts::{BLOB_AS_BUFFER, LIST_AS_BUFFER},
-    deep_hash::DeepHashChunk,
+    deep_hash::{DeepHashChunk, DeepHashVersion, Sha384Hasher},
     error::BundlrError,
 };
 use futures::{Stream, TryStream};
@@ -11,20 +10,28 @@ use futures::{Stream, TryStream};
 trait Foo: Stream<Item = anyhow::Result<Bytes>> + TryStream {}
 
 pub fn deep_hash_sync(chunk: DeepHashChunk) -> Result<Bytes, BundlrError> {
+    deep_hash_sync_with_version(chunk, DeepHashVersion::V1)
+}
+
+/// Same as [`deep_hash_sync`], with the given version of the algorithm
+pub fn deep_hash_sync_with_version(
+    chunk: DeepHashChunk,
+    version: DeepHashVersion,
+) -> Result<Bytes, BundlrError> {
     match chunk {
         DeepHashChunk::Chunk(b) => {
-            let tag = [BLOB_AS_BUFFER, b.len().to_string().as_bytes()].concat();
-            let c = [sha384hash(tag.into()), sha384hash(b)].concat();
-            Ok(Bytes::copy_from_slice(&sha384hash(c.into())))
+            let tag = [version.blob_marker(), b.len().to_string().as_bytes()].concat();
+            let c = [version.hash(&tag), version.hash(&b)].concat();
+            Ok(version.hash(&c))
         }
         DeepHashChunk::Chunks(chunks) => {
             // Be careful of truncation
             let len = chunks.len() as f64;
-            let tag = [LIST_AS_BUFFER, len.to_string().as_bytes()].concat();
+            let tag = [version.list_marker(), len.to_string().as_bytes()].concat();
 
-            let acc = sha384hash(tag.into());
+            let acc = version.hash(&tag);
 
-            deep_hash_chunks_sync(chunks, acc)
+            hash_chunks_sync(chunks, acc, version)
         }
         _ => Err(BundlrError::Unsupported(
             "Streaming is not supported for sync".to_owned(),
@@ -32,9 +39,14 @@ pub fn deep_hash_sync(chunk: DeepHashChunk) -> Result<Bytes, BundlrError> {
     }
 }
 
-pub fn deep_hash_chunks_sync(
+pub fn deep_hash_chunks_sync(chunks: Vec<DeepHashChunk>, acc: Bytes) -> Result<Bytes, BundlrError> {
+    hash_chunks_sync(chunks, acc, DeepHashVersion::V1)
+}
+
+fn hash_chunks_sync(
     mut chunks: Vec<DeepHashChunk>,
     acc: Bytes,
+    version: DeepHashVersion,
 ) -> Result<Bytes, BundlrError> {
     if chunks.is_empty() {
         return Ok(acc);
@@ -42,13 +54,27 @@ pub fn deep_hash_chunks_sync(
 
     let acc = Bytes::copy_from_slice(&acc);
 
-    let hash_pair = [acc, deep_hash_sync(chunks.remove(0))?].concat();
-    let new_acc = sha384hash(hash_pair.into());
-    deep_hash_chunks_sync(chunks, new_acc)
+    let hash_pair = [acc, deep_hash_sync_with_version(chunks.remove(0), version)?].concat();
+    let new_acc = version.hash(&hash_pair);
+    hash_chunks_sync(chunks, new_acc, version)
 }
 
-fn sha384hash(b: Bytes) -> Bytes {
-    let mut hasher = Sha384::new();
-    hasher.update(&b);
-    Bytes::copy_from_slice(&hasher.finalize())
+/// Deep hash of a list of blobs, hashing straight from the given slices instead of building
+/// `DeepHashChunk`s. Equivalent to `deep_hash_sync` over a `Chunks` of `Chunk`s.
+pub fn deep_hash_blobs(blobs: &[&[u8]]) -> Bytes {
+    let mut acc = sha384_parts(&[LIST_AS_BUFFER, blobs.len().to_string().as_bytes()]);
+    for blob in blobs {
+        let tag = sha384_parts(&[BLOB_AS_BUFFER, blob.len().to_string().as_bytes()]);
+        let hash = sha384_parts(&[&tag, &sha384_parts(&[blob])]);
+        acc = sha384_parts(&[&acc, &hash]);
+    }
+    Bytes::copy_from_slice(&acc)
+}
+
+fn sha384_parts(parts: &[&[u8]]) -> [u8; 48] {
+    let mut hasher = Sha384Hasher::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize()
 }
@@ -53,12 +53,18 @@ pub enum BundlrError {
     #[error("Tx status not confirmed")]
     TxStatusNotConfirmed,
 
-    #[error("Chunk size out of allowed range: {0} - {0}")]
+    #[error("Chunk size out of allowed range: {0} - {1}")]
     ChunkSizeOutOfRange(u64, u64),
 
+    #[error("Invalid chunk size {0}")]
+    InvalidChunkSize(u64),
+
     #[error("Error posting chunk: {0}")]
     PostChunkError(String),
 
+    #[error("Too many requests")]
+    TooManyRequests,
+
     #[error("No signature present")]
     NoSignature,
 
@@ -83,6 +89,15 @@ pub enum BundlrError {
     #[error("Upload error: {0}")]
     UploadError(String),
 
+    #[error("Item too large to be serialized")]
+    ItemTooLarge,
+
+    #[error("Item expired at height {0}")]
+    Expired(u64),
+
+    #[error("Downloaded data does not match its digest {0}")]
+    DigestMismatch(String),
+
     #[error("Unknown: {0}")]
     Unknown(String),
 
@@ -109,6 +124,60 @@ pub enum BundlrError {
 
     #[error("RecoveryError")]
     RecoveryError(RecoveryError),
+
+    #[error("{operation} failed: {source}")]
+    Context {
+        operation: String,
+        source: Box<BundlrError>,
+    },
+}
+
+impl BundlrError {
+    /// Wraps the error with the name of the operation that failed, unless it is already
+    /// wrapped with that same operation
+    pub fn with_context(self, operation: &str) -> Self {
+        match self {
+            BundlrError::Context {
+                operation: ref current,
+                ..
+            } if current == operation => self,
+            err => BundlrError::Context {
+                operation: operation.to_owned(),
+                source: Box::new(err),
+            },
+        }
+    }
+
+    /// Whether the operation may succeed if retried: network failures, rate limiting and server
+    /// errors are retryable, client errors aren't
+    pub fn is_retryable(&self) -> bool {
+        match self.root() {
+            BundlrError::RequestError(_) | BundlrError::TooManyRequests => true,
+            BundlrError::ResponseError(msg) => {
+                !msg.starts_with("Status: 4") || msg.starts_with("Status: 429")
+            }
+            _ => false,
+        }
+    }
+
+    /// The underlying error, without the operations context
+    pub fn root(&self) -> &BundlrError {
+        match self {
+            BundlrError::Context { source, .. } => source.root(),
+            err => err,
+        }
+    }
+}
+
+/// Adds the failed operation to errors, see [`BundlrError::with_context`]
+pub trait ErrorContext<T> {
+    fn context(self, operation: &str) -> Result<T, BundlrError>;
+}
+
+impl<T> ErrorContext<T> for Result<T, BundlrError> {
+    fn context(self, operation: &str) -> Result<T, BundlrError> {
+        self.map_err(|err| err.with_context(operation))
+    }
 }
 
 impl From<BuilderError> for BundlrError {
@@ -134,6 +203,9 @@ pub enum BuilderError {
     #[error("Fetch pub info error: {0}")]
     FetchPubInfoError(String),
 
+    #[error("Node version {node} is older than the required {required}")]
+    IncompatibleNodeVersion { node: String, required: String },
+
     #[error("Arweave Sdk error: {0}")]
     ArweaveSdkError(arweave_rs::error::Error),
 }
@@ -149,3 +221,44 @@ impl From<BundlrError> for BuilderError {
         Self::BundlrError(value.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BundlrError, ErrorContext};
+
+    #[test]
+    fn should_name_failed_operation() {
+        let res: Result<(), BundlrError> = Err(BundlrError::ResponseError("timeout".into()));
+        let err = res.context("upload").context("upload").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "upload failed: Response failed with the following error: timeout"
+        );
+        assert!(matches!(err.root(), BundlrError::ResponseError(_)));
+        assert_eq!(
+            err.with_context("fund").to_string(),
+            "fund failed: upload failed: Response failed with the following error: timeout"
+        );
+    }
+
+    #[test]
+    fn should_tell_retryable_errors() {
+        let status = |status: &str| BundlrError::ResponseError(format!("Status: {}:\"\"", status));
+        assert!(status("503 Service Unavailable").is_retryable());
+        assert!(status("429 Too Many Requests").is_retryable());
+        assert!(!status("400 Bad Request").is_retryable());
+        assert!(BundlrError::RequestError("timeout".into())
+            .with_context("withdraw")
+            .is_retryable());
+        assert!(!BundlrError::InvalidSignature.is_retryable());
+    }
+
+    #[test]
+    fn should_show_chunk_size_range() {
+        assert_eq!(
+            BundlrError::ChunkSizeOutOfRange(1, 10).to_string(),
+            "Chunk size out of allowed range: 1 - 10"
+        );
+    }
+}
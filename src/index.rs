@@ -1,7 +1,6 @@
 use bytes::Bytes;
 use derive_more::Display;
 use num_derive::FromPrimitive;
-use std::panic;
 
 use crate::Verifier;
 
@@ -23,10 +22,13 @@ use crate::AptosSigner;
 #[cfg(feature = "aptos")]
 use crate::MultiAptosSigner;
 
+#[cfg(feature = "sui")]
+use crate::SuiSigner;
+
 use crate::error::BundlrError;
 use crate::signers::typed_ethereum::TypedEthereumSigner;
 
-#[derive(FromPrimitive, Display, PartialEq, Eq, Debug, Clone)]
+#[derive(FromPrimitive, Display, PartialEq, Eq, Hash, Debug, Clone)]
 pub enum SignerMap {
     None = -1,
     Arweave = 1,
@@ -36,6 +38,7 @@ pub enum SignerMap {
     InjectedAptos = 5,
     MultiAptos = 6,
     TypedEthereum = 7,
+    Sui = 8,
     Cosmos, //TODO: assign constant
 }
 
@@ -62,12 +65,44 @@ impl From<u16> for SignerMap {
             5 => SignerMap::InjectedAptos,
             6 => SignerMap::MultiAptos,
             7 => SignerMap::TypedEthereum,
+            8 => SignerMap::Sui,
             _ => SignerMap::None,
         }
     }
 }
 
+/// Signature types supported by this build, i.e. those whose cargo feature is enabled. The
+/// others fail with [`BundlrError::Unsupported`].
+pub fn enabled_signers() -> Vec<SignerMap> {
+    SignerMap::ALL
+        .into_iter()
+        .filter(|signer| signer.get_config().is_ok())
+        .collect()
+}
+
 impl SignerMap {
+    /// Every signature type, whether enabled in this build or not
+    pub const ALL: [SignerMap; 9] = [
+        SignerMap::Arweave,
+        SignerMap::ED25519,
+        SignerMap::Ethereum,
+        SignerMap::Solana,
+        SignerMap::InjectedAptos,
+        SignerMap::MultiAptos,
+        SignerMap::TypedEthereum,
+        SignerMap::Sui,
+        SignerMap::Cosmos,
+    ];
+
+    /// Same as [`SignerMap::from`], but unknown signature types are an error instead of
+    /// [`SignerMap::None`]
+    pub fn try_from_u16(t: u16) -> Result<SignerMap, BundlrError> {
+        match SignerMap::from(t) {
+            SignerMap::None => Err(BundlrError::InvalidSignerType),
+            signer => Ok(signer),
+        }
+    }
+
     pub fn as_u16(&self) -> u16 {
         match self {
             SignerMap::Arweave => 1,
@@ -77,65 +112,147 @@ impl SignerMap {
             SignerMap::InjectedAptos => 5,
             SignerMap::MultiAptos => 6,
             SignerMap::TypedEthereum => 7,
+            SignerMap::Sui => 8,
             _ => u16::MAX,
         }
     }
 
-    pub fn get_config(&self) -> Config {
+    /// Stable name of the signature type, as in [`Config::sig_name`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            SignerMap::None => "none",
+            SignerMap::Arweave => "arweave",
+            SignerMap::ED25519 => "ed25519",
+            SignerMap::Ethereum => "ethereum",
+            SignerMap::Solana => "solana",
+            SignerMap::InjectedAptos => "injectedAptos",
+            SignerMap::MultiAptos => "multiAptos",
+            SignerMap::TypedEthereum => "typedEthereum",
+            SignerMap::Sui => "sui",
+            SignerMap::Cosmos => "cosmos",
+        }
+    }
+
+    /// Signature type with the given [`SignerMap::name`], if any
+    pub fn from_name(name: &str) -> Option<SignerMap> {
+        match name {
+            "arweave" => Some(SignerMap::Arweave),
+            "ed25519" => Some(SignerMap::ED25519),
+            "ethereum" => Some(SignerMap::Ethereum),
+            "solana" => Some(SignerMap::Solana),
+            "injectedAptos" => Some(SignerMap::InjectedAptos),
+            "multiAptos" => Some(SignerMap::MultiAptos),
+            "typedEthereum" => Some(SignerMap::TypedEthereum),
+            "sui" => Some(SignerMap::Sui),
+            "cosmos" => Some(SignerMap::Cosmos),
+            _ => None,
+        }
+    }
+
+    pub fn get_config(&self) -> Result<Config, BundlrError> {
         match *self {
             #[cfg(feature = "arweave")]
-            SignerMap::Arweave => Config {
+            SignerMap::Arweave => Ok(Config {
                 sig_length: 512,
                 pub_length: 512,
-                sig_name: "arweave".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "arweave"))]
+            SignerMap::Arweave => Err(BundlrError::Unsupported(
+                "feature arweave not enabled".to_owned(),
+            )),
             #[cfg(feature = "algorand")]
-            SignerMap::ED25519 => Config {
+            SignerMap::ED25519 => Ok(Config {
                 sig_length: ed25519_dalek::SIGNATURE_LENGTH,
                 pub_length: ed25519_dalek::PUBLIC_KEY_LENGTH,
-                sig_name: "ed25519".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "algorand"))]
+            SignerMap::ED25519 => Err(BundlrError::Unsupported(
+                "feature algorand not enabled".to_owned(),
+            )),
             #[cfg(any(feature = "ethereum", feature = "erc20"))]
-            SignerMap::Ethereum => Config {
+            SignerMap::Ethereum => Ok(Config {
                 sig_length: secp256k1::constants::COMPACT_SIGNATURE_SIZE + 1,
                 pub_length: secp256k1::constants::UNCOMPRESSED_PUBLIC_KEY_SIZE,
-                sig_name: "ethereum".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(any(feature = "ethereum", feature = "erc20")))]
+            SignerMap::Ethereum => Err(BundlrError::Unsupported(
+                "feature ethereum not enabled".to_owned(),
+            )),
             #[cfg(feature = "solana")]
-            SignerMap::Solana => Config {
+            SignerMap::Solana => Ok(Config {
                 sig_length: ed25519_dalek::SIGNATURE_LENGTH,
                 pub_length: ed25519_dalek::PUBLIC_KEY_LENGTH,
-                sig_name: "solana".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "solana"))]
+            SignerMap::Solana => Err(BundlrError::Unsupported(
+                "feature solana not enabled".to_owned(),
+            )),
             #[cfg(feature = "aptos")]
-            SignerMap::InjectedAptos => Config {
+            SignerMap::InjectedAptos => Ok(Config {
                 sig_length: ed25519_dalek::SIGNATURE_LENGTH,
                 pub_length: ed25519_dalek::PUBLIC_KEY_LENGTH,
-                sig_name: "injectedAptos".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "aptos"))]
+            SignerMap::InjectedAptos => Err(BundlrError::Unsupported(
+                "feature aptos not enabled".to_owned(),
+            )),
             #[cfg(feature = "aptos")]
-            SignerMap::MultiAptos => Config {
+            SignerMap::MultiAptos => Ok(Config {
                 sig_length: ed25519_dalek::SIGNATURE_LENGTH * 32 + 4, // max 32 64 byte signatures, +4 for 32-bit bitmap
                 pub_length: ed25519_dalek::PUBLIC_KEY_LENGTH * 32 + 1, // max 64 32 byte keys, +1 for 8-bit threshold value
-                sig_name: "multiAptos".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "aptos"))]
+            SignerMap::MultiAptos => Err(BundlrError::Unsupported(
+                "feature aptos not enabled".to_owned(),
+            )),
             #[cfg(feature = "cosmos")]
-            SignerMap::Cosmos => Config {
+            SignerMap::Cosmos => Ok(Config {
                 sig_length: secp256k1::constants::COMPACT_SIGNATURE_SIZE,
                 pub_length: secp256k1::constants::PUBLIC_KEY_SIZE,
-                sig_name: "cosmos".to_owned(),
-            },
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "cosmos"))]
+            SignerMap::Cosmos => Err(BundlrError::Unsupported(
+                "feature cosmos not enabled".to_owned(),
+            )),
             #[cfg(any(feature = "ethereum", feature = "erc20"))]
-            SignerMap::TypedEthereum => Config {
+            SignerMap::TypedEthereum => Ok(Config {
                 sig_length: secp256k1::constants::COMPACT_SIGNATURE_SIZE + 1,
                 pub_length: 42,
-                sig_name: "typedEthereum".to_owned(),
-            },
-            #[allow(unreachable_patterns)]
-            _ => panic!("{:?} get_config has no", self),
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(any(feature = "ethereum", feature = "erc20")))]
+            SignerMap::TypedEthereum => Err(BundlrError::Unsupported(
+                "feature ethereum not enabled".to_owned(),
+            )),
+            #[cfg(feature = "sui")]
+            SignerMap::Sui => Ok(Config {
+                sig_length: ed25519_dalek::SIGNATURE_LENGTH,
+                pub_length: ed25519_dalek::PUBLIC_KEY_LENGTH,
+                sig_name: self.name().to_owned(),
+            }),
+            #[cfg(not(feature = "sui"))]
+            SignerMap::Sui => Err(BundlrError::Unsupported(
+                "feature sui not enabled".to_owned(),
+            )),
+            SignerMap::None => Err(BundlrError::InvalidSignerType),
         }
     }
 
+    /// Size in bytes of the smallest item signed with this signer, i.e. an item without target,
+    /// anchor, tags nor data: signature type, signature, owner, the target and anchor presence
+    /// bytes, and the tag count and tags length
+    pub fn min_item_size(&self) -> Result<usize, BundlrError> {
+        let config = self.get_config()?;
+        Ok(2 + config.sig_length + config.pub_length + 2 + 16)
+    }
+
     pub fn verify(&self, pk: &[u8], message: &[u8], signature: &[u8]) -> Result<(), BundlrError> {
         match *self {
             #[cfg(feature = "arweave")]
@@ -144,50 +261,201 @@ impl SignerMap {
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(feature = "arweave"))]
+            SignerMap::Arweave => Err(BundlrError::Unsupported(
+                "feature arweave not enabled".to_owned(),
+            )),
             #[cfg(feature = "algorand")]
             SignerMap::ED25519 => Ed25519Signer::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(feature = "algorand"))]
+            SignerMap::ED25519 => Err(BundlrError::Unsupported(
+                "feature algorand not enabled".to_owned(),
+            )),
             #[cfg(any(feature = "ethereum", feature = "erc20"))]
             SignerMap::Ethereum => Secp256k1Signer::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(any(feature = "ethereum", feature = "erc20")))]
+            SignerMap::Ethereum => Err(BundlrError::Unsupported(
+                "feature ethereum not enabled".to_owned(),
+            )),
             #[cfg(feature = "solana")]
             SignerMap::Solana => Ed25519Signer::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(feature = "solana"))]
+            SignerMap::Solana => Err(BundlrError::Unsupported(
+                "feature solana not enabled".to_owned(),
+            )),
             #[cfg(feature = "aptos")]
             SignerMap::InjectedAptos => AptosSigner::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(feature = "aptos"))]
+            SignerMap::InjectedAptos => Err(BundlrError::Unsupported(
+                "feature aptos not enabled".to_owned(),
+            )),
             #[cfg(feature = "aptos")]
             SignerMap::MultiAptos => MultiAptosSigner::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(feature = "aptos"))]
+            SignerMap::MultiAptos => Err(BundlrError::Unsupported(
+                "feature aptos not enabled".to_owned(),
+            )),
             #[cfg(feature = "cosmos")]
             SignerMap::Cosmos => CosmosSigner::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
+            #[cfg(not(feature = "cosmos"))]
+            SignerMap::Cosmos => Err(BundlrError::Unsupported(
+                "feature cosmos not enabled".to_owned(),
+            )),
             #[cfg(any(feature = "ethereum", feature = "erc20"))]
             SignerMap::TypedEthereum => TypedEthereumSigner::verify(
                 Bytes::copy_from_slice(pk),
                 Bytes::copy_from_slice(message),
                 Bytes::copy_from_slice(signature),
             ),
-            #[allow(unreachable_patterns)]
-            _ => panic!("{:?} verify not implemented in SignerMap yet", self),
+            #[cfg(not(any(feature = "ethereum", feature = "erc20")))]
+            SignerMap::TypedEthereum => Err(BundlrError::Unsupported(
+                "feature ethereum not enabled".to_owned(),
+            )),
+            #[cfg(feature = "sui")]
+            SignerMap::Sui => SuiSigner::verify(
+                Bytes::copy_from_slice(pk),
+                Bytes::copy_from_slice(message),
+                Bytes::copy_from_slice(signature),
+            ),
+            #[cfg(not(feature = "sui"))]
+            SignerMap::Sui => Err(BundlrError::Unsupported(
+                "feature sui not enabled".to_owned(),
+            )),
+            SignerMap::None => Err(BundlrError::InvalidSignerType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enabled_signers, SignerMap};
+    use crate::error::BundlrError;
+
+    #[test]
+    fn should_reject_unknown_signer() {
+        assert!(matches!(
+            SignerMap::from(0).get_config(),
+            Err(BundlrError::InvalidSignerType)
+        ));
+        assert!(matches!(
+            SignerMap::None.verify(&[], &[], &[]),
+            Err(BundlrError::InvalidSignerType)
+        ));
+    }
+
+    #[test]
+    fn should_compute_min_item_size() {
+        #[cfg(feature = "arweave")]
+        assert_eq!(SignerMap::Arweave.min_item_size().unwrap(), 1044);
+        #[cfg(feature = "algorand")]
+        assert_eq!(SignerMap::ED25519.min_item_size().unwrap(), 116);
+        assert!(matches!(
+            SignerMap::None.min_item_size(),
+            Err(BundlrError::InvalidSignerType)
+        ));
+    }
+
+    #[test]
+    fn should_parse_known_signature_types_only() {
+        assert_eq!(SignerMap::try_from_u16(1).unwrap(), SignerMap::Arweave);
+        assert_eq!(SignerMap::try_from_u16(8).unwrap(), SignerMap::Sui);
+        for t in 1..=8 {
+            assert_eq!(SignerMap::try_from_u16(t).unwrap().as_u16(), t);
+        }
+        assert!(matches!(
+            SignerMap::try_from_u16(0),
+            Err(BundlrError::InvalidSignerType)
+        ));
+        assert!(matches!(
+            SignerMap::try_from_u16(u16::MAX),
+            Err(BundlrError::InvalidSignerType)
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "solana"))]
+    fn should_reject_disabled_signer() {
+        match SignerMap::Solana.get_config() {
+            Err(BundlrError::Unsupported(msg)) => assert_eq!(msg, "feature solana not enabled"),
+            _ => panic!("Solana config should be unsupported"),
+        }
+        assert!(matches!(
+            SignerMap::Solana.verify(&[], &[], &[]),
+            Err(BundlrError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn should_list_enabled_signers() {
+        let expected = [
+            (SignerMap::Arweave, cfg!(feature = "arweave")),
+            (SignerMap::ED25519, cfg!(feature = "algorand")),
+            (
+                SignerMap::Ethereum,
+                cfg!(any(feature = "ethereum", feature = "erc20")),
+            ),
+            (SignerMap::Solana, cfg!(feature = "solana")),
+            (SignerMap::InjectedAptos, cfg!(feature = "aptos")),
+            (SignerMap::MultiAptos, cfg!(feature = "aptos")),
+            (
+                SignerMap::TypedEthereum,
+                cfg!(any(feature = "ethereum", feature = "erc20")),
+            ),
+            (SignerMap::Sui, cfg!(feature = "sui")),
+            (SignerMap::Cosmos, cfg!(feature = "cosmos")),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(signer, _)| signer)
+        .collect::<Vec<_>>();
+
+        assert_eq!(enabled_signers(), expected);
+    }
+
+    #[test]
+    fn should_round_trip_names() {
+        let signers = [
+            SignerMap::Arweave,
+            SignerMap::ED25519,
+            SignerMap::Ethereum,
+            SignerMap::Solana,
+            SignerMap::InjectedAptos,
+            SignerMap::MultiAptos,
+            SignerMap::TypedEthereum,
+            SignerMap::Sui,
+            SignerMap::Cosmos,
+        ];
+        for signer in signers {
+            assert_eq!(SignerMap::from_name(signer.name()), Some(signer.clone()));
+            if let Ok(config) = signer.get_config() {
+                assert_eq!(config.sig_name, signer.name());
+            }
         }
+        assert_eq!(SignerMap::from_name("none"), None);
+        assert_eq!(SignerMap::from_name("Arweave"), None);
     }
 }
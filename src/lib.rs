@@ -6,6 +6,7 @@ mod transaction;
 #[cfg(feature = "build-binary")]
 pub mod client;
 
+pub mod bundle;
 pub mod bundlr;
 pub mod consts;
 pub mod currency;
@@ -13,25 +14,42 @@ pub mod deep_hash;
 pub mod deep_hash_sync;
 pub mod error;
 pub mod index;
+pub mod manifest;
+pub mod metrics;
+pub mod price_oracle;
+pub mod receipt;
 pub mod tags;
+#[cfg(any(test, feature = "testing"))]
+pub mod test_support;
+pub mod transport;
 pub mod upload;
 pub mod utils;
 pub mod verify;
 
 pub use bundlr::{Bundlr, BundlrBuilder};
+pub use signers::multi::MultiSigner;
 pub use signers::Signer;
 pub use transaction::bundlr::BundlrTx;
+pub use transaction::rng::{RngSource, SeededRng, SystemRng};
 pub use verify::Verifier;
 
 #[cfg(feature = "arweave")]
 pub use signers::arweave::ArweaveSigner;
 
-#[cfg(any(feature = "solana", feature = "algorand"))]
+#[cfg(any(
+    feature = "solana",
+    feature = "algorand",
+    feature = "aptos",
+    feature = "sui"
+))]
 pub use signers::ed25519::Ed25519Signer;
 
 #[cfg(any(feature = "ethereum", feature = "erc20"))]
 pub use signers::secp256k1::Secp256k1Signer;
 
+#[cfg(any(feature = "ethereum", feature = "erc20"))]
+pub use signers::typed_ethereum::{TypedDataDomain, TypedEthereumSigner};
+
 #[cfg(feature = "cosmos")]
 pub use signers::cosmos::CosmosSigner;
 
@@ -40,3 +58,6 @@ pub use signers::aptos::AptosSigner;
 
 #[cfg(feature = "aptos")]
 pub use signers::aptos::MultiAptosSigner;
+
+#[cfg(feature = "sui")]
+pub use signers::sui::SuiSigner;
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::BundlrError;
+
+/// Content type of the items holding a path manifest
+pub const MANIFEST_CONTENT_TYPE: &str = "application/x.arweave-manifest+json";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub id: String,
+}
+
+/// Arweave path manifest, mapping the relative paths of the files of an uploaded directory to
+/// the ids of their items
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Manifest {
+    pub manifest: String,
+    pub version: String,
+    pub paths: BTreeMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    /// Creates a manifest from `(relative path, item id)` entries
+    pub fn new(entries: &[(String, String)]) -> Self {
+        Manifest {
+            manifest: "arweave/paths".to_owned(),
+            version: "0.1.0".to_owned(),
+            paths: entries
+                .iter()
+                .map(|(path, id)| (path.clone(), ManifestEntry { id: id.clone() }))
+                .collect(),
+        }
+    }
+
+    /// Serializes the manifest as the json data of its item
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BundlrError> {
+        serde_json::to_vec(self).map_err(|err| BundlrError::ParseError(err.to_string()))
+    }
+}
+
+/// Size in bytes of the serialized manifest of `(relative path, item id)` entries. Ids are only
+/// known once the files are signed, but all have the same length, so placeholders of 43
+/// characters give the exact size. The manifest is uploaded as its own item, so directory cost
+/// estimates have to include it.
+pub fn estimated_size(entries: &[(String, String)]) -> u64 {
+    Manifest::new(entries)
+        .to_bytes()
+        .map(|bytes| bytes.len() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimated_size, Manifest};
+
+    #[test]
+    fn should_estimate_manifest_size() {
+        assert_eq!(
+            estimated_size(&[]),
+            r#"{"manifest":"arweave/paths","version":"0.1.0","paths":{}}"#.len() as u64
+        );
+
+        let placeholder = "x".repeat(43);
+        let entries = (0..1000)
+            .map(|i| (format!("assets/file-{}.png", i), placeholder.clone()))
+            .collect::<Vec<_>>();
+        let manifest = Manifest::new(&entries).to_bytes().unwrap();
+
+        assert_eq!(estimated_size(&entries), manifest.len() as u64);
+        assert!(estimated_size(&entries) > 1000 * 43);
+    }
+
+    #[test]
+    fn should_serialize_paths() {
+        let entries = [("index.html".to_owned(), "id".to_owned())];
+        let manifest = Manifest::new(&entries).to_bytes().unwrap();
+
+        assert_eq!(
+            String::from_utf8(manifest).unwrap(),
+            r#"{"manifest":"arweave/paths","version":"0.1.0","paths":{"index.html":{"id":"id"}}}"#
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+/// Receives per-operation metrics from [`crate::Bundlr`] and its [`crate::upload::Uploader`],
+/// to be forwarded to e.g. Prometheus or statsd. Every method does nothing by default, so
+/// implementations only override the operations they care about. Methods are called inline
+/// with the operation, so they should return quickly.
+pub trait Metrics: Send + Sync {
+    /// Called after each transaction is posted, with its size in bytes
+    fn record_upload(&self, _bytes: u64, _duration: Duration, _success: bool) {}
+
+    /// Called after each funding, with the funded amount in the currency base unit. The
+    /// duration includes sending the transaction on the currency network.
+    fn record_fund(&self, _amount: u64, _duration: Duration, _success: bool) {}
+
+    /// Called after each attempt to post a chunk of a chunked upload, with its size in bytes
+    fn record_chunk(&self, _bytes: u64, _duration: Duration, _success: bool) {}
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{consts::PRICE_ORACLE_DEFAULT_URL, currency::CurrencyType, error::BundlrError};
+
+#[derive(Deserialize)]
+struct UsdPrice {
+    usd: f64,
+}
+
+/// Fetches currencies USD prices from a CoinGecko compatible api
+#[derive(Clone)]
+pub struct PriceOracle {
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl Default for PriceOracle {
+    fn default() -> Self {
+        let url = Url::parse(PRICE_ORACLE_DEFAULT_URL).unwrap(); //Unwrap ok, never fails
+        Self::new(url, reqwest::Client::new())
+    }
+}
+
+impl PriceOracle {
+    pub fn new(url: Url, client: reqwest::Client) -> Self {
+        Self { url, client }
+    }
+
+    /// Gets the USD price of a whole unit of each currency. Currencies the oracle has no price
+    /// for, like ERC20 tokens, are left out of the result.
+    pub async fn usd_prices(
+        &self,
+        currencies: &[CurrencyType],
+    ) -> Result<HashMap<CurrencyType, f64>, BundlrError> {
+        let ids = currencies
+            .iter()
+            .filter_map(|currency| oracle_id(*currency).map(|id| (*currency, id)))
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = ids.iter().map(|(_, id)| *id).collect::<Vec<_>>().join(",");
+        let prices = self
+            .client
+            .get(
+                self.url
+                    .join("simple/price")
+                    .map_err(|err| BundlrError::ParseError(err.to_string()))?,
+            )
+            .query(&[("ids", query.as_str()), ("vs_currencies", "usd")])
+            .send()
+            .await
+            .map_err(|err| BundlrError::RequestError(err.to_string()))?
+            .json::<HashMap<String, UsdPrice>>()
+            .await
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|(currency, id)| prices.get(id).map(|price| (currency, price.usd)))
+            .collect())
+    }
+}
+
+fn oracle_id(currency: CurrencyType) -> Option<&'static str> {
+    match currency {
+        CurrencyType::Arweave => Some("arweave"),
+        CurrencyType::Solana => Some("solana"),
+        CurrencyType::Ethereum => Some("ethereum"),
+        CurrencyType::Cosmos => Some("cosmos"),
+        CurrencyType::Erc20 => None,
+    }
+}
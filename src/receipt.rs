@@ -0,0 +1,220 @@
+use bytes::Bytes;
+use data_encoding::BASE64URL_NOPAD;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    deep_hash::DeepHashChunk, deep_hash_sync::deep_hash_sync, error::BundlrError, ArweaveSigner,
+    Verifier,
+};
+
+/// Acknowledgement returned by a Bundlr node for an uploaded transaction, signed by the node
+/// and optionally by validators. Only `id` is always present, nodes leave the other fields out
+/// of unsigned receipts.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    pub id: String,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub public: String,
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default)]
+    pub deadline_height: u64,
+    #[serde(default)]
+    pub block: u64,
+    #[serde(default)]
+    pub validator_signatures: Vec<String>,
+}
+
+/// Signed receipt requested by the tags of an item, see [`crate::BundlrTx::receipt_request`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceiptRequest {
+    /// Height from the item `Deadline-Height` tag, which the receipt deadline must not exceed
+    pub deadline_height: Option<u64>,
+}
+
+fn decode_base64(value: &str) -> Result<Bytes, BundlrError> {
+    BASE64URL_NOPAD
+        .decode(value.as_bytes())
+        .map(Bytes::from)
+        .map_err(|err| BundlrError::Base64Error(err.to_string()))
+}
+
+impl Receipt {
+    pub fn from_json(json: &str) -> Result<Self, BundlrError> {
+        serde_json::from_str(json).map_err(|err| BundlrError::ParseError(err.to_string()))
+    }
+
+    /// Deep hash of the receipt fields, which is the message signed by the node and validators
+    pub fn receipt_hash(&self) -> Result<Bytes, BundlrError> {
+        let fields = DeepHashChunk::Chunks(vec![
+            DeepHashChunk::Chunk("Bundlr".into()),
+            DeepHashChunk::Chunk(self.version.clone().into()),
+            DeepHashChunk::Chunk(self.id.clone().into()),
+            DeepHashChunk::Chunk(self.deadline_height.to_string().into()),
+            DeepHashChunk::Chunk(self.timestamp.to_string().into()),
+        ]);
+        deep_hash_sync(fields)
+    }
+
+    /// Whether the network is still below the height by which the node promised the item would
+    /// be final
+    pub fn is_within_deadline(&self, current_height: u64) -> bool {
+        current_height < self.deadline_height
+    }
+
+    /// Checks the node signature over the receipt hash, against the node key in `public`
+    pub fn verify(&self) -> Result<(), BundlrError> {
+        ArweaveSigner::verify(
+            decode_base64(&self.public)?,
+            self.receipt_hash()?,
+            decode_base64(&self.signature)?,
+        )
+    }
+
+    /// Checks that the receipt was signed by the node with public key `node_pubkey`, which makes
+    /// `timestamp` a proof of when the node accepted the item `id`. Unlike
+    /// [`Receipt::verify`], the key embedded in the receipt is not trusted. Nodes only sign
+    /// receipts requested with [`crate::Bundlr::send_transaction_with_receipt`].
+    pub fn verify_timestamp(&self, node_pubkey: &Bytes) -> Result<(), BundlrError> {
+        if self.signature.is_empty() {
+            return Err(BundlrError::InvalidSignature);
+        }
+        ArweaveSigner::verify(
+            node_pubkey.clone(),
+            self.receipt_hash()?,
+            decode_base64(&self.signature)?,
+        )
+    }
+
+    /// Checks that at least `threshold` of the given validator public keys have a valid
+    /// signature over the receipt hash in `validator_signatures`. Each public key is counted
+    /// only once.
+    pub fn verify_validators(
+        &self,
+        validator_pubkeys: &[Bytes],
+        threshold: usize,
+    ) -> Result<(), BundlrError> {
+        let message = self.receipt_hash()?;
+        let signatures = self
+            .validator_signatures
+            .iter()
+            .map(|sig| decode_base64(sig))
+            .collect::<Result<Vec<Bytes>, BundlrError>>()?;
+
+        let mut pubkeys = validator_pubkeys.to_vec();
+        pubkeys.sort();
+        pubkeys.dedup();
+        let valid = pubkeys
+            .into_iter()
+            .filter(|pk| {
+                signatures.iter().any(|sig| {
+                    ArweaveSigner::verify(pk.clone(), message.clone(), sig.clone()).is_ok()
+                })
+            })
+            .count();
+
+        if valid >= threshold {
+            Ok(())
+        } else {
+            Err(BundlrError::InvalidSignature)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use data_encoding::BASE64URL_NOPAD;
+
+    use super::Receipt;
+    use crate::error::BundlrError;
+
+    #[test]
+    fn should_check_deadline() {
+        let receipt = Receipt {
+            deadline_height: 1000,
+            ..Receipt::default()
+        };
+
+        assert!(receipt.is_within_deadline(999));
+        assert!(!receipt.is_within_deadline(1000));
+        assert!(!receipt.is_within_deadline(1001));
+    }
+
+    fn receipt_signed_by_node() -> (Receipt, Bytes) {
+        let data = std::fs::read_to_string("res/test_receipt.json").expect("Unable to read file");
+        let mut receipt = Receipt::from_json(&data).expect("Unable to parse json file");
+        let node_pubkey = BASE64URL_NOPAD
+            .decode(receipt.public.as_bytes())
+            .unwrap()
+            .into();
+        receipt.validator_signatures = vec![receipt.signature.clone()];
+        (receipt, node_pubkey)
+    }
+
+    #[test]
+    fn should_verify_receipt() {
+        let (mut receipt, _) = receipt_signed_by_node();
+        assert!(receipt.verify().is_ok());
+
+        receipt.block += 1;
+        assert!(receipt.verify().is_ok());
+        receipt.deadline_height += 1;
+        assert!(receipt.verify().is_err());
+    }
+
+    #[test]
+    fn should_verify_timestamp() {
+        let (mut receipt, node_pubkey) = receipt_signed_by_node();
+        assert!(receipt.verify_timestamp(&node_pubkey).is_ok());
+        assert!(receipt
+            .verify_timestamp(&Bytes::from(vec![0u8; 512]))
+            .is_err());
+
+        receipt.timestamp += 1;
+        assert!(receipt.verify_timestamp(&node_pubkey).is_err());
+
+        let unsigned = Receipt::default();
+        assert!(matches!(
+            unsigned.verify_timestamp(&node_pubkey),
+            Err(BundlrError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn should_fail_to_parse_invalid_receipt() {
+        assert!(matches!(
+            Receipt::from_json("{ \"timestamp\": 1 }"),
+            Err(BundlrError::ParseError(_))
+        ));
+        // Unsigned receipts only hold the id
+        let unsigned = Receipt::from_json("{ \"id\": \"id\" }").unwrap();
+        assert_eq!(unsigned.id, "id");
+        assert!(unsigned.signature.is_empty());
+    }
+
+    #[test]
+    fn should_verify_validator_quorum() {
+        let (receipt, node_pubkey) = receipt_signed_by_node();
+        assert!(receipt.verify_validators(&[node_pubkey], 1).is_ok());
+    }
+
+    #[test]
+    fn should_fail_validator_quorum_below_threshold() {
+        let (receipt, node_pubkey) = receipt_signed_by_node();
+        let unknown_pubkey = Bytes::from(vec![0u8; 512]);
+
+        assert!(receipt.verify_validators(&[], 0).is_ok());
+        assert!(receipt
+            .verify_validators(&[node_pubkey.clone(), node_pubkey.clone()], 2)
+            .is_err());
+        assert!(receipt
+            .verify_validators(&[node_pubkey, unknown_pubkey], 2)
+            .is_err());
+    }
+}
@@ -7,22 +7,80 @@ use bytes::Bytes;
 use ed25519_dalek::{Keypair, Verifier, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
 use num::Integer;
 
+/// Nonce appended by Aptos wallets to signed messages, used unless overridden
+const DEFAULT_NONCE: &str = "bundlr";
+
 pub struct AptosSigner {
     signer: Ed25519Signer,
+    nonce: String,
 }
 
 impl AptosSigner {
     pub fn new(keypair: Keypair) -> Self {
         Self {
             signer: Ed25519Signer::new(keypair),
+            nonce: DEFAULT_NONCE.to_owned(),
         }
     }
 
     pub fn from_base58(s: &str) -> Result<Self, BundlrError> {
         Ok(Self {
             signer: Ed25519Signer::from_base58(s)?,
+            nonce: DEFAULT_NONCE.to_owned(),
         })
     }
+
+    /// Replaces the nonce of the signed message, `bundlr` by default, to interoperate with
+    /// wallets and nodes expecting another one
+    pub fn with_nonce(mut self, nonce: &str) -> Self {
+        self.nonce = nonce.to_owned();
+        self
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// Verifies a signature made with this signer's nonce, instead of the default one used by
+    /// [`VerifierTrait::verify`]. Items signed with a custom nonce don't record it, so they must
+    /// be verified with [`crate::BundlrTx::verify_with_aptos_nonce`].
+    pub fn verify_with_nonce(
+        &self,
+        pk: Bytes,
+        message: Bytes,
+        signature: Bytes,
+    ) -> Result<(), BundlrError> {
+        verify_aptos_message(pk, message, signature, &self.nonce)
+    }
+}
+
+/// Message as signed by Aptos wallets
+fn aptos_message(message: &[u8], nonce: &str) -> Bytes {
+    Bytes::from(
+        [
+            b"APTOS\nmessage: ".as_ref(),
+            message,
+            b"\nnonce: ",
+            nonce.as_bytes(),
+        ]
+        .concat(),
+    )
+}
+
+pub(crate) fn verify_aptos_message(
+    pk: Bytes,
+    message: Bytes,
+    signature: Bytes,
+    nonce: &str,
+) -> Result<(), BundlrError> {
+    let public_key =
+        ed25519_dalek::PublicKey::from_bytes(&pk).map_err(BundlrError::ED25519Error)?;
+    let sig =
+        ed25519_dalek::Signature::from_bytes(&signature).map_err(BundlrError::ED25519Error)?;
+
+    public_key
+        .verify(&aptos_message(&message, nonce), &sig)
+        .map_err(|_err| BundlrError::InvalidSignature)
 }
 
 const SIG_TYPE: SignerMap = SignerMap::InjectedAptos;
@@ -31,11 +89,7 @@ const PUB_LENGTH: u16 = PUBLIC_KEY_LENGTH as u16;
 
 impl SignerTrait for AptosSigner {
     fn sign(&self, message: bytes::Bytes) -> Result<bytes::Bytes, crate::error::BundlrError> {
-        let aptos_message =
-            Bytes::copy_from_slice(&[b"APTOS\nmessage: ".as_ref(), &message[..]].concat());
-        let nonce = Bytes::from(b"\nnonce: bundlr".to_vec());
-        let full_msg = Bytes::from([aptos_message, nonce].concat());
-        self.signer.sign(full_msg)
+        self.signer.sign(aptos_message(&message, &self.nonce))
     }
 
     fn pub_key(&self) -> bytes::Bytes {
@@ -59,18 +113,7 @@ impl VerifierTrait for AptosSigner {
         message: Bytes,
         signature: Bytes,
     ) -> Result<(), crate::error::BundlrError> {
-        let public_key =
-            ed25519_dalek::PublicKey::from_bytes(&pk).map_err(BundlrError::ED25519Error)?;
-        let sig =
-            ed25519_dalek::Signature::from_bytes(&signature).map_err(BundlrError::ED25519Error)?;
-        let aptos_message =
-            Bytes::copy_from_slice(&[b"APTOS\nmessage: ".as_ref(), &message[..]].concat());
-        let nonce = Bytes::from(b"\nnonce: bundlr".to_vec());
-        let full_msg = Bytes::from([aptos_message, nonce].concat());
-
-        public_key
-            .verify(&full_msg, &sig)
-            .map_err(|_err| BundlrError::InvalidSignature)
+        verify_aptos_message(pk, message, signature, DEFAULT_NONCE)
     }
 }
 
@@ -169,7 +212,7 @@ impl VerifierTrait for MultiAptosSigner {
 
 #[cfg(test)]
 mod tests {
-    use crate::{AptosSigner, Signer, Verifier};
+    use crate::{test_support::TEST_KEYPAIR, AptosSigner, Signer, Verifier};
     use bytes::Bytes;
     use ed25519_dalek::Keypair;
 
@@ -177,8 +220,7 @@ mod tests {
     fn should_sign_and_verify() {
         let msg = Bytes::from(b"Message".to_vec());
 
-        let base58_secret_key = "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
-        let signer = AptosSigner::from_base58(base58_secret_key).unwrap();
+        let signer = AptosSigner::from_base58(TEST_KEYPAIR).unwrap();
         let sig = signer.sign(msg.clone()).unwrap();
         let pub_key = signer.pub_key();
         println!("{:?}", pub_key.to_vec());
@@ -198,6 +240,25 @@ mod tests {
         assert!(AptosSigner::verify(pub_key, msg, sig).is_ok());
     }
 
+    #[test]
+    fn should_sign_and_verify_with_nonce() {
+        let msg = Bytes::from(b"Message".to_vec());
+        let signer = AptosSigner::from_base58(TEST_KEYPAIR)
+            .unwrap()
+            .with_nonce("irys");
+        let sig = signer.sign(msg.clone()).unwrap();
+        let pub_key = signer.pub_key();
+
+        assert!(signer
+            .verify_with_nonce(pub_key.clone(), msg.clone(), sig.clone())
+            .is_ok());
+        assert!(AptosSigner::verify(pub_key.clone(), msg.clone(), sig.clone()).is_err());
+        let other_nonce = AptosSigner::from_base58(TEST_KEYPAIR)
+            .unwrap()
+            .with_nonce("other");
+        assert!(other_nonce.verify_with_nonce(pub_key, msg, sig).is_err());
+    }
+
     #[test]
     fn should_sign_and_verify_multisig() {
         //TODO: implement
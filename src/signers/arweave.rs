@@ -65,25 +65,11 @@ mod tests {
     use std::{path::PathBuf, str::FromStr};
 
     use crate::{
-        deep_hash::DeepHashChunk, deep_hash_sync::deep_hash_sync, ArweaveSigner, Signer, Verifier,
+        deep_hash::DeepHashChunk, deep_hash_sync::deep_hash_sync, receipt::Receipt, ArweaveSigner,
+        Signer, Verifier,
     };
     use bytes::Bytes;
     use data_encoding::BASE64URL_NOPAD;
-    use serde::{Deserialize, Serialize};
-
-    //TODO: remove this when receipt included
-    #[derive(Serialize, Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    pub struct Receipt {
-        pub id: String,
-        pub timestamp: u64,
-        pub version: String,
-        pub public: String,
-        pub signature: String,
-        pub deadline_height: u64,
-        pub block: u64,
-        pub validator_signatures: Vec<String>,
-    }
 
     #[test]
     fn should_sign_and_verify() {
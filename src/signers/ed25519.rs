@@ -77,7 +77,7 @@ impl VerifierTrait for Ed25519Signer {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Ed25519Signer, Signer, Verifier};
+    use crate::{test_support::TEST_KEYPAIR, Ed25519Signer, Signer, Verifier};
     use bytes::Bytes;
     use ed25519_dalek::Keypair;
 
@@ -85,8 +85,7 @@ mod tests {
     fn should_sign_and_verify() {
         let msg = Bytes::from(b"Message".to_vec());
 
-        let base58_secret_key = "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
-        let signer = Ed25519Signer::from_base58(base58_secret_key).unwrap();
+        let signer = Ed25519Signer::from_base58(TEST_KEYPAIR).unwrap();
         let sig = signer.sign(msg.clone()).unwrap();
         let pub_key = signer.pub_key();
         println!("{:?}", pub_key.to_vec());
@@ -7,10 +7,18 @@ pub mod aptos;
 pub mod arweave;
 #[cfg(feature = "cosmos")]
 pub mod cosmos;
-#[cfg(any(feature = "solana", feature = "algorand", feature = "aptos"))]
+#[cfg(any(
+    feature = "solana",
+    feature = "algorand",
+    feature = "aptos",
+    feature = "sui"
+))]
 pub mod ed25519;
+pub mod multi;
 #[cfg(any(feature = "ethereum", feature = "erc20"))]
 pub mod secp256k1;
+#[cfg(feature = "sui")]
+pub mod sui;
 #[cfg(any(feature = "ethereum", feature = "erc20"))]
 pub mod typed_ethereum;
 
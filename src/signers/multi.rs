@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::{error::BundlrError, index::SignerMap, Signer};
+
+/// Holds signers of several signature types and signs with the active one, which can be
+/// switched at runtime
+pub struct MultiSigner {
+    signers: HashMap<SignerMap, Box<dyn Signer>>,
+    active: SignerMap,
+}
+
+impl MultiSigner {
+    /// Creates a multi signer holding `signer`, which becomes the active one
+    pub fn new(signer: Box<dyn Signer>) -> Self {
+        let active = signer.sig_type();
+        let mut signers = HashMap::new();
+        signers.insert(active.clone(), signer);
+        Self { signers, active }
+    }
+
+    /// Adds `signer`, replacing any held signer of the same signature type. The active signer
+    /// is unchanged, unless it is the one being replaced.
+    pub fn add_signer(&mut self, signer: Box<dyn Signer>) {
+        self.signers.insert(signer.sig_type(), signer);
+    }
+
+    /// Makes the signer of `sig_type` the active one
+    pub fn set_active(&mut self, sig_type: SignerMap) -> Result<(), BundlrError> {
+        if !self.signers.contains_key(&sig_type) {
+            return Err(BundlrError::InvalidSignerType);
+        }
+        self.active = sig_type;
+        Ok(())
+    }
+
+    pub fn active(&self) -> &SignerMap {
+        &self.active
+    }
+
+    fn active_signer(&self) -> &dyn Signer {
+        // The active signature type always has a signer, see `new` and `set_active`
+        self.signers[&self.active].as_ref()
+    }
+}
+
+impl Signer for MultiSigner {
+    fn sign(&self, message: Bytes) -> Result<Bytes, BundlrError> {
+        self.active_signer().sign(message)
+    }
+
+    fn sig_type(&self) -> SignerMap {
+        self.active_signer().sig_type()
+    }
+
+    fn get_sig_length(&self) -> u16 {
+        self.active_signer().get_sig_length()
+    }
+
+    fn get_pub_length(&self) -> u16 {
+        self.active_signer().get_pub_length()
+    }
+
+    fn pub_key(&self) -> Bytes {
+        self.active_signer().pub_key()
+    }
+}
+
+#[cfg(all(test, feature = "solana", feature = "ethereum"))]
+mod tests {
+    use secp256k1::SecretKey;
+
+    use crate::{
+        error::BundlrError, index::SignerMap, tags::Tag, test_support::test_signer, BundlrTx,
+        Secp256k1Signer, Signer,
+    };
+
+    use super::MultiSigner;
+
+    #[tokio::test]
+    async fn should_sign_with_active_signer() {
+        let ed25519 = test_signer();
+        let secp256k1 = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        let ed25519_pub_key = ed25519.pub_key();
+        let secp256k1_pub_key = secp256k1.pub_key();
+
+        let mut signer = MultiSigner::new(Box::new(ed25519));
+        signer.add_signer(Box::new(secp256k1));
+        assert_eq!(signer.sig_type(), SignerMap::ED25519);
+        assert_eq!(signer.pub_key(), ed25519_pub_key);
+
+        let mut tx =
+            BundlrTx::new(vec![], b"hello".to_vec(), vec![Tag::new("name", "value")]).unwrap();
+        tx.sign(&signer).await.unwrap();
+        assert!(tx.verify().await.is_ok());
+
+        signer.set_active(SignerMap::Ethereum).unwrap();
+        assert_eq!(signer.get_sig_length(), 65);
+        assert_eq!(signer.pub_key(), secp256k1_pub_key);
+
+        let mut tx =
+            BundlrTx::new(vec![], b"hello".to_vec(), vec![Tag::new("name", "value")]).unwrap();
+        tx.sign(&signer).await.unwrap();
+        assert!(tx.verify().await.is_ok());
+        assert_eq!(tx.get_owner(), secp256k1_pub_key.as_ref());
+
+        assert!(matches!(
+            signer.set_active(SignerMap::Arweave),
+            Err(BundlrError::InvalidSignerType)
+        ));
+        assert_eq!(signer.active(), &SignerMap::Ethereum);
+    }
+}
@@ -0,0 +1,164 @@
+use std::array::TryFromSliceError;
+
+use crate::error::BundlrError;
+use crate::Signer as SignerTrait;
+use crate::Verifier as VerifierTrait;
+use crate::{index::SignerMap, Ed25519Signer};
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use bytes::Bytes;
+use data_encoding::BASE64;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Verifier, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Intent scope `PersonalMessage`, intent version `V0`, app id `Sui`
+const PERSONAL_MESSAGE_INTENT: [u8; 3] = [3, 0, 0];
+const ED25519_FLAG: u8 = 0x00;
+
+pub struct SuiSigner {
+    signer: Ed25519Signer,
+}
+
+impl SuiSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self {
+            signer: Ed25519Signer::new(keypair),
+        }
+    }
+
+    pub fn from_base58(s: &str) -> Result<Self, BundlrError> {
+        Ok(Self {
+            signer: Ed25519Signer::from_base58(s)?,
+        })
+    }
+
+    /// Loads a key as stored in the Sui keystore: base64 of the scheme flag followed by the
+    /// 32 byte secret key. Only the ed25519 scheme is supported.
+    pub fn from_keystore(s: &str) -> Result<Self, BundlrError> {
+        let k = BASE64
+            .decode(s.as_bytes())
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+        let (flag, secret) = k
+            .split_first()
+            .ok_or_else(|| BundlrError::InvalidKey("Empty Sui keystore entry".to_owned()))?;
+        if *flag != ED25519_FLAG {
+            return Err(BundlrError::InvalidKey(format!(
+                "Unsupported Sui signature scheme flag {}",
+                flag
+            )));
+        }
+        let secret: &[u8; 32] = secret
+            .try_into()
+            .map_err(|err: TryFromSliceError| BundlrError::ParseError(err.to_string()))?;
+
+        let secret = SecretKey::from_bytes(secret).map_err(BundlrError::ED25519Error)?;
+        let public = PublicKey::from(&secret);
+        Ok(Self::new(Keypair { secret, public }))
+    }
+}
+
+/// Blake2b-256 digest of the Sui personal message intent over the BCS encoded message
+fn intent_message_digest(message: &[u8]) -> Bytes {
+    let mut hasher = Blake2b256::new();
+    hasher.update(PERSONAL_MESSAGE_INTENT);
+    let mut len = message.len();
+    while len >= 0x80 {
+        hasher.update([(len as u8 & 0x7f) | 0x80]);
+        len >>= 7;
+    }
+    hasher.update([len as u8]);
+    hasher.update(message);
+    Bytes::copy_from_slice(&hasher.finalize())
+}
+
+const SIG_TYPE: SignerMap = SignerMap::Sui;
+const SIG_LENGTH: u16 = SIGNATURE_LENGTH as u16;
+const PUB_LENGTH: u16 = PUBLIC_KEY_LENGTH as u16;
+
+impl SignerTrait for SuiSigner {
+    fn sign(&self, message: bytes::Bytes) -> Result<bytes::Bytes, crate::error::BundlrError> {
+        self.signer.sign(intent_message_digest(&message))
+    }
+
+    fn pub_key(&self) -> bytes::Bytes {
+        self.signer.pub_key()
+    }
+
+    fn sig_type(&self) -> SignerMap {
+        SIG_TYPE
+    }
+    fn get_sig_length(&self) -> u16 {
+        SIG_LENGTH
+    }
+    fn get_pub_length(&self) -> u16 {
+        PUB_LENGTH
+    }
+}
+
+impl VerifierTrait for SuiSigner {
+    fn verify(
+        pk: Bytes,
+        message: Bytes,
+        signature: Bytes,
+    ) -> Result<(), crate::error::BundlrError> {
+        let public_key =
+            ed25519_dalek::PublicKey::from_bytes(&pk).map_err(BundlrError::ED25519Error)?;
+        let sig =
+            ed25519_dalek::Signature::from_bytes(&signature).map_err(BundlrError::ED25519Error)?;
+
+        public_key
+            .verify(&intent_message_digest(&message), &sig)
+            .map_err(|_err| BundlrError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_support::TEST_KEYPAIR, Ed25519Signer, Signer, SuiSigner, Verifier};
+    use bytes::Bytes;
+
+    use super::intent_message_digest;
+
+    #[test]
+    fn should_hash_intent_message() {
+        assert_eq!(
+            data_encoding::HEXLOWER.encode(&intent_message_digest(b"Message")),
+            "ccc2b79e8f67e2e4b2876a4ea5fd9614e29051d550c289afeb0ad440fdabab37"
+        );
+        // Messages of 128 bytes or more use a multi-byte ULEB128 length prefix
+        assert_eq!(
+            data_encoding::HEXLOWER.encode(&intent_message_digest(&[0u8; 200])),
+            "2f8b296ac768d9846a28b03e6ec8212114a6f1125257ffcbb335554006b8c4aa"
+        );
+    }
+
+    #[test]
+    fn should_sign_and_verify() {
+        let msg = Bytes::from(b"Message".to_vec());
+
+        let signer = SuiSigner::from_base58(TEST_KEYPAIR).unwrap();
+        let sig = signer.sign(msg.clone()).unwrap();
+        let pub_key = signer.pub_key();
+
+        assert!(SuiSigner::verify(pub_key.clone(), msg.clone(), sig.clone()).is_ok());
+        assert!(
+            SuiSigner::verify(pub_key.clone(), Bytes::from(b"Other".to_vec()), sig.clone())
+                .is_err()
+        );
+        // The intent prefix makes the signature differ from a plain ed25519 one
+        assert!(Ed25519Signer::verify(pub_key, msg, sig).is_err());
+    }
+
+    #[test]
+    fn should_load_keystore_entry() {
+        let keystore = "AAEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEB";
+        let signer = SuiSigner::from_keystore(keystore).unwrap();
+        let msg = Bytes::from(b"Message".to_vec());
+        let sig = signer.sign(msg.clone()).unwrap();
+        assert!(SuiSigner::verify(signer.pub_key(), msg, sig).is_ok());
+
+        let secp256k1_keystore = "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEB";
+        assert!(SuiSigner::from_keystore(secp256k1_keystore).is_err());
+    }
+}
@@ -9,65 +9,64 @@ use secp256k1::constants::COMPACT_SIGNATURE_SIZE;
 use serde_json::{from_str, json};
 use web3::signing::recover;
 
+/// EIP-712 domain the typed data is signed over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedDataDomain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: Option<u64>,
+}
+
+impl Default for TypedDataDomain {
+    fn default() -> Self {
+        Self {
+            name: "Bundlr".to_string(),
+            version: "1".to_string(),
+            chain_id: None,
+        }
+    }
+}
+
+#[derive(Default)]
 pub struct TypedEthereumSigner {
     //signer: Secp256k1Signer,
     //address: Vec<u8>,
+    domain: TypedDataDomain,
 }
 
-const SIG_TYPE: SignerMap = SignerMap::Ethereum;
-const SIG_LENGTH: u16 = (COMPACT_SIGNATURE_SIZE + 1) as u16;
-const PUB_LENGTH: u16 = 42;
-
-impl Signer for TypedEthereumSigner {
-    fn pub_key(&self) -> bytes::Bytes {
-        todo!();
+impl TypedEthereumSigner {
+    pub fn new(domain: TypedDataDomain) -> Self {
+        Self { domain }
     }
 
-    fn sign(&self, _message: bytes::Bytes) -> Result<bytes::Bytes, crate::error::BundlrError> {
-        todo!();
-    }
-
-    fn sig_type(&self) -> SignerMap {
-        SIG_TYPE
-    }
-    fn get_sig_length(&self) -> u16 {
-        SIG_LENGTH
-    }
-    fn get_pub_length(&self) -> u16 {
-        PUB_LENGTH
+    pub fn domain(&self) -> &TypedDataDomain {
+        &self.domain
     }
-}
-
-impl Verifier for TypedEthereumSigner {
-    fn verify(
-        public_key: Bytes,
-        message: Bytes,
-        signature: Bytes,
-    ) -> Result<(), crate::error::BundlrError> {
-        let address = String::from_utf8(public_key.to_vec()).map_err(|err| {
-            BundlrError::ParseError(format!(
-                "Error parsing address from bytes to string: {}",
-                err
-            ))
-        })?;
 
+    fn typed_data_hash(&self, address: &str, message: &[u8]) -> Result<[u8; 32], BundlrError> {
         let mut hex_message: String = "0x".to_owned();
-        for i in 0..message.len() {
-            let byte = message[i];
+        for byte in message {
             hex_message += &format!("{:02X}", byte);
         }
 
+        let mut domain = json!({
+            "name": self.domain.name,
+            "version": self.domain.version
+        });
+        let mut domain_types = vec![
+            json!({ "name": "name", "type": "string" }),
+            json!({ "name": "version", "type": "string" }),
+        ];
+        if let Some(chain_id) = self.domain.chain_id {
+            domain["chainId"] = json!(format!("{:#x}", chain_id));
+            domain_types.push(json!({ "name": "chainId", "type": "uint256" }));
+        }
+
         let json = json!({
             "primaryType": "Bundlr",
-            "domain": {
-                "name": "Bundlr",
-                "version": "1"
-            },
+            "domain": domain,
             "types": {
-                "EIP712Domain": [
-                    { "name": "name", "type": "string" },
-                    { "name": "version", "type": "string" }
-                ],
+                "EIP712Domain": domain_types,
                 "Bundlr": [
                     { "name": "Transaction hash", "type": "bytes" },
                     { "name": "address", "type": "address" }
@@ -82,7 +81,25 @@ impl Verifier for TypedEthereumSigner {
         let typed_data = from_str::<EIP712>(&json.to_string()).map_err(|err| {
             BundlrError::ParseError(format!("Error parsing EIP712 json object: {}", err))
         })?;
-        let data = hash_structured_data(typed_data).map_err(BundlrError::Eip712Error)?;
+        hash_structured_data(typed_data).map_err(BundlrError::Eip712Error)
+    }
+
+    /// Verifies a signature over typed data using this signer's domain, instead of the default
+    /// one used by [`Verifier::verify`]
+    pub fn verify_with_domain(
+        &self,
+        public_key: Bytes,
+        message: Bytes,
+        signature: Bytes,
+    ) -> Result<(), BundlrError> {
+        let address = String::from_utf8(public_key.to_vec()).map_err(|err| {
+            BundlrError::ParseError(format!(
+                "Error parsing address from bytes to string: {}",
+                err
+            ))
+        })?;
+
+        let data = self.typed_data_hash(&address, &message)?;
         let recovered_address = recover(&data, &signature[0..64], signature[64] as i32 - 27)
             .map_err(BundlrError::RecoveryError)?;
 
@@ -96,7 +113,93 @@ impl Verifier for TypedEthereumSigner {
     }
 }
 
+const SIG_TYPE: SignerMap = SignerMap::Ethereum;
+const SIG_LENGTH: u16 = (COMPACT_SIGNATURE_SIZE + 1) as u16;
+const PUB_LENGTH: u16 = 42;
+
+impl Signer for TypedEthereumSigner {
+    fn pub_key(&self) -> bytes::Bytes {
+        todo!();
+    }
+
+    fn sign(&self, _message: bytes::Bytes) -> Result<bytes::Bytes, crate::error::BundlrError> {
+        todo!();
+    }
+
+    fn sig_type(&self) -> SignerMap {
+        SIG_TYPE
+    }
+    fn get_sig_length(&self) -> u16 {
+        SIG_LENGTH
+    }
+    fn get_pub_length(&self) -> u16 {
+        PUB_LENGTH
+    }
+}
+
+impl Verifier for TypedEthereumSigner {
+    fn verify(
+        public_key: Bytes,
+        message: Bytes,
+        signature: Bytes,
+    ) -> Result<(), crate::error::BundlrError> {
+        TypedEthereumSigner::default().verify_with_domain(public_key, message, signature)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    //TODO: implement sign and tests
+    //TODO: test sign once implemented
+    use bytes::Bytes;
+    use web3::signing::{Key, SecretKey, SecretKeyRef};
+
+    use super::{TypedDataDomain, TypedEthereumSigner};
+    use crate::Verifier;
+
+    fn sign_typed(signer: &TypedEthereumSigner, key: &SecretKey, message: &[u8]) -> (Bytes, Bytes) {
+        let key = SecretKeyRef::new(key);
+        let address = format!("{:?}", key.address());
+        let hash = signer.typed_data_hash(&address, message).unwrap();
+        let signature = key.sign_message(&hash).unwrap();
+
+        let mut sig = signature.r.as_bytes().to_vec();
+        sig.extend_from_slice(signature.s.as_bytes());
+        sig.push(signature.v as u8 + 27);
+        (Bytes::from(address), Bytes::from(sig))
+    }
+
+    #[test]
+    fn should_verify_with_chain_id_domain() {
+        let key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let message = Bytes::from(b"Hello, Bundlr!".to_vec());
+        let signer = TypedEthereumSigner::new(TypedDataDomain {
+            chain_id: Some(137),
+            ..TypedDataDomain::default()
+        });
+
+        let (address, sig) = sign_typed(&signer, &key, &message);
+        assert!(signer
+            .verify_with_domain(address.clone(), message.clone(), sig.clone())
+            .is_ok());
+        // The default domain has no chain id, so the signature doesn't match it
+        assert!(
+            TypedEthereumSigner::verify(address.clone(), message.clone(), sig.clone()).is_err()
+        );
+        let other_chain = TypedEthereumSigner::new(TypedDataDomain {
+            chain_id: Some(1),
+            ..TypedDataDomain::default()
+        });
+        assert!(other_chain
+            .verify_with_domain(address, message, sig)
+            .is_err());
+    }
+
+    #[test]
+    fn should_verify_with_default_domain() {
+        let key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let message = Bytes::from(b"Hello, Bundlr!".to_vec());
+
+        let (address, sig) = sign_typed(&TypedEthereumSigner::default(), &key, &message);
+        assert!(TypedEthereumSigner::verify(address, message, sig).is_ok());
+    }
 }
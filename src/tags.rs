@@ -1,5 +1,8 @@
+use std::{collections::HashMap, fs, path::Path};
+
 use avro_rs::{from_avro_datum, to_avro_datum, Schema};
 use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +23,177 @@ impl Tag {
     }
 }
 
+pub const APP_NAME_TAG: &str = "App-Name";
+pub const APP_VERSION_TAG: &str = "App-Version";
+/// Digest of the item data, as `sha256-<base64>`, checked by [`Bundlr::download_streaming`]
+///
+/// [`Bundlr::download_streaming`]: crate::Bundlr::download_streaming
+pub const DATA_DIGEST_TAG: &str = "Data-Digest";
+/// Network height after which the node should reject the item
+pub const DEADLINE_HEIGHT_TAG: &str = "Deadline-Height";
+/// Set to `true` when the uploader wants a signed receipt for the item, see
+/// [`crate::BundlrTx::receipt_request`]
+pub const RECEIPT_REQUEST_TAG: &str = "Receipt-Request";
+/// Format of the item, always [`DATA_PROTOCOL`], see [`crate::BundlrBuilder::signer_tags`]
+pub const DATA_PROTOCOL_TAG: &str = "Data-Protocol";
+pub const DATA_PROTOCOL: &str = "ANS-104";
+/// Name of the signature type of the item, as in [`crate::index::Config::sig_name`]
+pub const SIGNATURE_TYPE_TAG: &str = "Signature-Type";
+
+/// Tags that should appear at most once in an item, see [`BundlrTx::normalize_tags`]
+///
+/// [`BundlrTx::normalize_tags`]: crate::BundlrTx::normalize_tags
+pub const SINGLETON_TAGS: &[&str] = &["Content-Type", "Content-SHA256"];
+
+/// Whether uploaded files get tagged with `File-Name`, `File-Size` and `File-Modified` (RFC3339)
+/// taken from their file system metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileMetadataTags {
+    #[default]
+    Exclude,
+    Include,
+}
+
+/// Builds the `File-Name`, `File-Size` and `File-Modified` tags for the file at `path`
+pub fn file_metadata_tags(path: &Path) -> Result<Vec<Tag>, BundlrError> {
+    let metadata = fs::metadata(path)?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let modified: DateTime<Utc> = metadata.modified()?.into();
+
+    Ok(vec![
+        Tag::new("File-Name", &name),
+        Tag::new("File-Size", &metadata.len().to_string()),
+        Tag::new(
+            "File-Modified",
+            &modified.to_rfc3339_opts(SecondsFormat::Secs, true),
+        ),
+    ])
+}
+
+/// Content type of data that couldn't be identified
+pub const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Leading bytes identifying common file formats, and their content type
+const CONTENT_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x00asm", "application/wasm"),
+    (b"<!DOCTYPE html", "text/html"),
+    (b"<html", "text/html"),
+    (b"<svg", "image/svg+xml"),
+];
+
+/// Guesses the content type of `data` from its leading bytes, for files whose extension
+/// doesn't tell it
+pub fn guess_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    CONTENT_SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+        .map(|(_, content_type)| *content_type)
+}
+
+/// Bounds on the tags of a parsed item, so that untrusted items can't declare an arbitrary
+/// amount of tags to decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagLimits {
+    pub max_tags: u64,
+    pub max_tags_bytes: u64,
+}
+
+impl Default for TagLimits {
+    fn default() -> Self {
+        Self {
+            max_tags: 1024,
+            max_tags_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Differences between two sets of tags, compared by name. See [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagDiff {
+    /// Desired tags whose name is not in the current tags
+    pub added: Vec<Tag>,
+    /// Current tags whose name is not in the desired tags
+    pub removed: Vec<Tag>,
+    /// Current and desired tags sharing a name but not their value
+    pub changed: Vec<(Tag, Tag)>,
+}
+
+impl TagDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares `current` tags against `desired` ones by name. When a name appears several times,
+/// only its last occurrence is compared.
+pub fn diff(current: &[Tag], desired: &[Tag]) -> TagDiff {
+    let current_by_name = last_by_name(current);
+    let desired_by_name = last_by_name(desired);
+
+    let mut diff = TagDiff::default();
+    for tag in unique_by_name(desired, &desired_by_name) {
+        match current_by_name.get(tag.name.as_str()) {
+            None => diff.added.push(tag.clone()),
+            Some(current) if current.value != tag.value => {
+                diff.changed.push(((*current).clone(), tag.clone()))
+            }
+            Some(_) => (),
+        }
+    }
+    diff.removed = unique_by_name(current, &current_by_name)
+        .filter(|tag| !desired_by_name.contains_key(tag.name.as_str()))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// Last occurrence of each tag name
+fn last_by_name(tags: &[Tag]) -> HashMap<&str, &Tag> {
+    tags.iter().map(|tag| (tag.name.as_str(), tag)).collect()
+}
+
+/// Tags keeping only the last occurrence of each name, in order
+fn unique_by_name<'a>(
+    tags: &'a [Tag],
+    by_name: &'a HashMap<&str, &Tag>,
+) -> impl Iterator<Item = &'a Tag> {
+    tags.iter()
+        .filter(move |tag| std::ptr::eq(by_name[tag.name.as_str()], *tag))
+}
+
+/// Order given to tags by [`canonicalize`]. ANS-104 signs and serializes tags in the order they
+/// are given, so the same tags in another order make a different item with a different id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagOrder {
+    /// Tags are kept in insertion order, which callers must then keep stable themselves
+    #[default]
+    Preserve,
+    /// Tags are sorted byte-wise by name, then by value
+    Sorted,
+}
+
+/// Puts `tags` in the given `order`, so that items built from the same set of tags serialize
+/// to the same bytes whatever order the tags were collected in
+pub fn canonicalize(tags: &mut [Tag], order: TagOrder) {
+    match order {
+        TagOrder::Preserve => (),
+        TagOrder::Sorted => tags.sort_by(|a, b| (&a.name, &a.value).cmp(&(&b.name, &b.value))),
+    }
+}
+
 const SCHEMA_STR: &str = r#"{
     "type": "array",
     "items": {
@@ -33,9 +207,20 @@ const SCHEMA_STR: &str = r#"{
 }"#;
 
 lazy_static! {
+    static ref PARSED_TAGS_SCHEMA: Result<Schema, String> =
+        Schema::parse_str(SCHEMA_STR).map_err(|err| err.to_string());
+    /// Avro schema of the tags. Panics on access if the schema is invalid, prefer [`schema`].
     pub static ref TAGS_SCHEMA: Schema = Schema::parse_str(SCHEMA_STR).unwrap();
 }
 
+/// Avro schema of the tags, parsed once. Fails with `BundlrError::ParseError` instead of
+/// panicking if the schema is invalid.
+pub fn schema() -> Result<&'static Schema, BundlrError> {
+    PARSED_TAGS_SCHEMA
+        .as_ref()
+        .map_err(|err| BundlrError::ParseError(err.clone()))
+}
+
 // const TAGS_READER: Reader<'static, Vec<Tag>> = Reader::with_schema(&TAGS_SCHEMA, Vec::<Tag>::new());
 // const TAGS_WRITER: Writer<'static, Vec<Tag>> = Writer::new(&TAGS_SCHEMA, Vec::new());
 
@@ -50,7 +235,7 @@ pub trait AvroDecode {
 impl AvroEncode for Vec<Tag> {
     fn encode(&self) -> Result<Bytes, BundlrError> {
         let v = avro_rs::to_value(self)?;
-        to_avro_datum(&TAGS_SCHEMA, v)
+        to_avro_datum(schema()?, v)
             .map(|v| v.into())
             .map_err(|_| BundlrError::NoBytesLeft)
     }
@@ -59,7 +244,8 @@ impl AvroEncode for Vec<Tag> {
 impl AvroDecode for &mut [u8] {
     fn decode(&mut self) -> Result<Vec<Tag>, BundlrError> {
         let x = self.to_vec();
-        let v = from_avro_datum(&TAGS_SCHEMA, &mut x.as_slice(), Some(&TAGS_SCHEMA))
+        let schema = schema()?;
+        let v = from_avro_datum(schema, &mut x.as_slice(), Some(schema))
             .map_err(|_| BundlrError::InvalidTagEncoding)?;
         avro_rs::from_value(&v).map_err(|_| BundlrError::InvalidTagEncoding)
     }
@@ -74,9 +260,117 @@ impl From<avro_rs::DeError> for BundlrError {
 #[cfg(test)]
 mod tests {
 
+    use std::path::Path;
+
     use crate::tags::{AvroDecode, AvroEncode};
 
-    use super::Tag;
+    use super::{
+        canonicalize, diff, file_metadata_tags, guess_content_type, schema, Tag, TagDiff, TagOrder,
+    };
+
+    #[test]
+    fn should_guess_content_type() {
+        assert_eq!(
+            guess_content_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some("image/png")
+        );
+        assert_eq!(guess_content_type(b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(
+            guess_content_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(guess_content_type(b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(guess_content_type(b"plain text"), None);
+        assert_eq!(guess_content_type(&[]), None);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let tags = vec![Tag::new("b", "1"), Tag::new("a", "2"), Tag::new("a", "1")];
+        let mut reversed = tags.clone();
+        reversed.reverse();
+
+        let mut sorted = tags.clone();
+        canonicalize(&mut sorted, TagOrder::Sorted);
+        assert_eq!(
+            sorted,
+            vec![Tag::new("a", "1"), Tag::new("a", "2"), Tag::new("b", "1")]
+        );
+        canonicalize(&mut reversed, TagOrder::Sorted);
+        assert_eq!(sorted.encode().unwrap(), reversed.encode().unwrap());
+
+        let mut preserved = tags.clone();
+        canonicalize(&mut preserved, TagOrder::Preserve);
+        assert_eq!(preserved, tags);
+        assert_eq!(preserved.encode().unwrap(), tags.encode().unwrap());
+        assert_ne!(preserved.encode().unwrap(), sorted.encode().unwrap());
+    }
+
+    #[test]
+    fn should_preserve_tag_order_through_encoding() {
+        let tags = vec![
+            Tag::new("z", "1"),
+            Tag::new("a", "2"),
+            Tag::new("Content-Type", "text/plain"),
+            Tag::new("a", "1"),
+            Tag::new("a", "1"),
+            Tag::new("empty", ""),
+            Tag::new("ünïcode", "値"),
+        ];
+        let encoded = tags.encode().unwrap();
+        let mut bytes = encoded.to_vec();
+        let decoded = (&mut bytes[..]).decode().unwrap();
+
+        assert_eq!(decoded, tags);
+        assert_eq!(decoded.encode().unwrap(), encoded);
+    }
+
+    #[test]
+    fn should_parse_tags_schema() {
+        assert!(schema().is_ok());
+    }
+
+    #[test]
+    fn test_diff() {
+        let current = vec![
+            Tag::new("Content-Type", "text/plain"),
+            Tag::new("name", "a"),
+            Tag::new("stale", "1"),
+            Tag::new("name", "b"),
+        ];
+        let desired = vec![
+            Tag::new("Content-Type", "text/html"),
+            Tag::new("name", "b"),
+            Tag::new("fresh", "1"),
+        ];
+
+        assert_eq!(
+            diff(&current, &desired),
+            TagDiff {
+                added: vec![Tag::new("fresh", "1")],
+                removed: vec![Tag::new("stale", "1")],
+                changed: vec![(
+                    Tag::new("Content-Type", "text/plain"),
+                    Tag::new("Content-Type", "text/html")
+                )],
+            }
+        );
+        assert!(diff(&current, &current).is_empty());
+        assert_eq!(diff(&[], &desired).added, desired);
+    }
+
+    #[test]
+    fn test_file_metadata_tags() {
+        let path = Path::new("res/test_image.jpg");
+        let tags = file_metadata_tags(path).unwrap();
+        let size = std::fs::metadata(path).unwrap().len().to_string();
+
+        assert_eq!(tags[0], Tag::new("File-Name", "test_image.jpg"));
+        assert_eq!(tags[1], Tag::new("File-Size", &size));
+        assert_eq!(tags[2].name, "File-Modified");
+        assert!(chrono::DateTime::parse_from_rfc3339(&tags[2].value).is_ok());
+        assert!(file_metadata_tags(Path::new("res/missing_file")).is_err());
+    }
 
     #[test]
     fn test_bytes() {
@@ -0,0 +1,182 @@
+use std::{fs, path::Path};
+
+use bytes::BufMut;
+use data_encoding::BASE64URL_NOPAD;
+use sha2::{Digest, Sha256};
+
+use crate::{error::BundlrError, tags::Tag, utils::u256_le, BundlrTx, Signer};
+
+/// Anchor used for every generated item, so fixtures are reproducible for deterministic signers.
+pub const FIXED_ANCHOR: [u8; 32] = [0u8; 32];
+
+/// Base58 Ed25519 keypair the tests sign with
+pub const TEST_KEYPAIR: &str =
+    "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
+
+/// Ed25519 signer with [`TEST_KEYPAIR`]
+#[cfg(any(
+    feature = "solana",
+    feature = "algorand",
+    feature = "aptos",
+    feature = "sui"
+))]
+pub fn test_signer() -> crate::Ed25519Signer {
+    crate::Ed25519Signer::from_base58(TEST_KEYPAIR).expect("TEST_KEYPAIR is a valid keypair")
+}
+
+/// Path of the file `name` in a temporary directory of the test process, created if missing, so
+/// that files written by tests stay out of the repository
+pub fn temp_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("bundlr-sdk-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Could not create the temporary directory");
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+/// Signs and serializes a data item with any signer, using [`FIXED_ANCHOR`] as anchor.
+pub async fn make_item(
+    signer: &dyn Signer,
+    data: &[u8],
+    tags: &[Tag],
+) -> Result<Vec<u8>, BundlrError> {
+    let mut tx = BundlrTx::new(vec![], data.to_vec(), tags.to_vec())?;
+    tx.set_anchor(FIXED_ANCHOR.to_vec())?;
+    tx.sign(signer).await?;
+    tx.as_bytes()
+}
+
+/// Wraps already serialized data items into a bundle: item count, header table and items.
+pub fn make_bundle(items: &[Vec<u8>]) -> Result<Vec<u8>, BundlrError> {
+    let mut headers = Vec::with_capacity(64 * items.len());
+    for item in items {
+        let tx = BundlrTx::from_bytes(item.clone())?;
+        headers.put(&u256_le(item.len() as u64)[..]);
+        headers.put(&Sha256::digest(tx.get_signarure())[..]);
+    }
+
+    let mut bundle =
+        Vec::with_capacity(32 + headers.len() + items.iter().map(Vec::len).sum::<usize>());
+    bundle.put(&u256_le(items.len() as u64)[..]);
+    bundle.put(&headers[..]);
+    for item in items {
+        bundle.put(&item[..]);
+    }
+    Ok(bundle)
+}
+
+/// Signers with fixed keys for [`dump_vectors`]. Only deterministic signature schemes are
+/// listed, as randomized ones (e.g. Arweave's RSA-PSS) can't produce stable vectors.
+fn vector_signers() -> Result<Vec<Box<dyn Signer>>, BundlrError> {
+    let signers: [Option<Box<dyn Signer>>; 2] = [
+        #[cfg(any(feature = "solana", feature = "algorand"))]
+        Some(Box::new(crate::Ed25519Signer::from_base58(TEST_KEYPAIR)?)),
+        #[cfg(not(any(feature = "solana", feature = "algorand")))]
+        None,
+        #[cfg(any(feature = "ethereum", feature = "erc20"))]
+        Some(Box::new(crate::Secp256k1Signer::new(
+            secp256k1::SecretKey::from_slice(b"00000000000000000000000000000000")
+                .map_err(BundlrError::Secp256k1Error)?,
+        ))),
+        #[cfg(not(any(feature = "ethereum", feature = "erc20")))]
+        None,
+    ];
+    Ok(signers.into_iter().flatten().collect())
+}
+
+/// Writes interop test vectors to `out_dir`, for other ANS-104 implementations to check their
+/// output against: for each enabled deterministic signer, `<signer name>.bin` holds a signed
+/// item built with a fixed key and [`FIXED_ANCHOR`], and `<signer name>.json` its expected id,
+/// owner, anchor, tags and data, base64url encoded. Regenerating the vectors is byte-stable.
+pub async fn dump_vectors(out_dir: &Path) -> Result<(), BundlrError> {
+    fs::create_dir_all(out_dir)?;
+    let tags = vec![
+        Tag::new("App-Name", "rust-sdk-vectors"),
+        Tag::new("Content-Type", "text/plain"),
+    ];
+
+    for signer in vector_signers()? {
+        let item = make_item(signer.as_ref(), b"hello", &tags).await?;
+        let tx = BundlrTx::from_bytes(item.clone())?;
+        let expected = serde_json::json!({
+            "signatureType": signer.sig_type().as_u16(),
+            "id": BASE64URL_NOPAD.encode(&Sha256::digest(tx.get_signarure())),
+            "owner": BASE64URL_NOPAD.encode(tx.get_owner()),
+            "anchor": BASE64URL_NOPAD.encode(tx.get_anchor()),
+            "tags": tx
+                .get_tags()
+                .iter()
+                .map(|tag| serde_json::json!({ "name": tag.name, "value": tag.value }))
+                .collect::<Vec<_>>(),
+            "data": BASE64URL_NOPAD.encode(b"hello"),
+        });
+        let expected = serde_json::to_string_pretty(&expected)
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+
+        let name = signer.sig_type().name();
+        fs::write(out_dir.join(format!("{}.bin", name)), &item)?;
+        fs::write(out_dir.join(format!("{}.json", name)), expected + "\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::SecretKey;
+
+    use std::{fs, path::Path};
+
+    use super::{dump_vectors, make_bundle, make_item, temp_path, test_signer};
+    use crate::{error::BundlrError, tags::Tag, verify::file::verify_file_bundle, Secp256k1Signer};
+
+    #[tokio::test]
+    async fn generated_fixtures_should_verify() -> Result<(), BundlrError> {
+        let path = &temp_path("test_support_bundle");
+        let ed25519 = test_signer();
+        let secp256k1 = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        let tags = vec![Tag::new("name", "value")];
+
+        let items = vec![
+            make_item(&ed25519, b"hello", &tags).await?,
+            make_item(&secp256k1, b"hello", &tags).await?,
+            make_item(&ed25519, b"", &[]).await?,
+        ];
+        assert_eq!(items[0], make_item(&ed25519, b"hello", &tags).await?);
+
+        std::fs::write(path, make_bundle(&items)?)?;
+        let verified = verify_file_bundle(path.to_string()).await?;
+        assert_eq!(verified.len(), items.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dumped_vectors_should_be_stable() -> Result<(), BundlrError> {
+        let first = temp_path("test_vectors_first");
+        let first = Path::new(&first);
+        let second = temp_path("test_vectors_second");
+        let second = Path::new(&second);
+        dump_vectors(first).await?;
+        dump_vectors(second).await?;
+
+        let mut names = fs::read_dir(first)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<Result<Vec<_>, _>>()?;
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "ed25519.bin",
+                "ed25519.json",
+                "ethereum.bin",
+                "ethereum.json"
+            ]
+        );
+        for name in names {
+            assert_eq!(fs::read(first.join(&name))?, fs::read(second.join(&name))?);
+        }
+
+        let mut item = crate::BundlrTx::from_bytes(fs::read(first.join("ethereum.bin"))?)?;
+        assert!(item.verify().await.is_ok());
+        Ok(())
+    }
+}
@@ -1,24 +1,48 @@
 use async_stream::try_stream;
 use bytes::{BufMut, Bytes};
-use futures::Stream;
-use ring::rand::SecureRandom;
+use data_encoding::BASE64URL_NOPAD;
+use futures::{future, stream, Stream, StreamExt};
+use sha2::{Digest, Sha256};
 use std::cmp;
+use std::collections::HashSet;
 use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::pin::Pin;
+#[cfg(feature = "memmap")]
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 use crate::consts::{CHUNK_SIZE, DATAITEM_AS_BUFFER, ONE_AS_BUFFER};
 use crate::deep_hash::{deep_hash, DeepHashChunk};
-use crate::deep_hash_sync::deep_hash_sync;
+use crate::deep_hash_sync::deep_hash_blobs;
 use crate::error::BundlrError;
 use crate::index::{Config, SignerMap};
+use crate::receipt::ReceiptRequest;
 use crate::signers::Signer;
-use crate::tags::{AvroDecode, AvroEncode, Tag};
+use crate::tags::{
+    AvroDecode, AvroEncode, Tag, TagLimits, APP_NAME_TAG, APP_VERSION_TAG, DEADLINE_HEIGHT_TAG,
+    RECEIPT_REQUEST_TAG,
+};
+use crate::transaction::rng::{RngSource, SystemRng};
 use crate::utils::read_offset;
 
 enum Data {
     None,
     Bytes(Vec<u8>),
-    Stream(Pin<Box<dyn Stream<Item = anyhow::Result<Bytes>>>>),
+    /// Data stream along with its length in bytes
+    Stream(Pin<Box<dyn Stream<Item = anyhow::Result<Bytes>>>>, u64),
+}
+
+/// Fields of a serialized item, borrowed from its buffer
+struct ItemFields<'a> {
+    signature_type: SignerMap,
+    signature: &'a [u8],
+    owner: &'a [u8],
+    target: &'a [u8],
+    anchor: &'a [u8],
+    number_of_tags: u64,
+    tags: &'a [u8],
+    data_start: usize,
 }
 
 pub struct BundlrTx {
@@ -28,17 +52,37 @@ pub struct BundlrTx {
     target: Vec<u8>,
     anchor: Vec<u8>,
     tags: Vec<Tag>,
+    /// Encoding of `tags` to hash and serialize verbatim instead of encoding them again, see
+    /// [`BundlrTx::new_with_encoded_tags`]. Parsed items keep the tag bytes they were read from.
+    encoded_tags: Option<Bytes>,
     data: Data,
+    /// Deep hash message, kept once computed so that a streamed data is only hashed once
+    message: Option<Bytes>,
+}
+
+/// Sums the sizes of the serialized item sections, failing if the total doesn't fit in memory
+fn serialized_length(sizes: &[u64]) -> Result<usize, BundlrError> {
+    sizes
+        .iter()
+        .try_fold(0u64, |total, size| total.checked_add(*size))
+        .and_then(|total| usize::try_from(total).ok())
+        .ok_or(BundlrError::ItemTooLarge)
 }
 
 impl BundlrTx {
     pub fn new(target: Vec<u8>, data: Vec<u8>, tags: Vec<Tag>) -> Result<Self, BundlrError> {
+        Self::new_with_rng(target, data, tags, &mut SystemRng::new())
+    }
+
+    /// Same as [`BundlrTx::new`], but the anchor is generated from `rng`
+    pub fn new_with_rng(
+        target: Vec<u8>,
+        data: Vec<u8>,
+        tags: Vec<Tag>,
+        rng: &mut impl RngSource,
+    ) -> Result<Self, BundlrError> {
         let mut randoms: [u8; 32] = [0; 32];
-        let sr = ring::rand::SystemRandom::new();
-        match sr.fill(&mut randoms) {
-            Ok(()) => (),
-            Err(err) => return Err(BundlrError::Unknown(err.to_string())),
-        }
+        rng.fill(&mut randoms)?;
         let anchor = randoms.to_vec();
 
         Ok(BundlrTx {
@@ -48,12 +92,44 @@ impl BundlrTx {
             target,
             anchor,
             tags,
+            encoded_tags: None,
             data: Data::Bytes(data),
+            message: None,
         })
     }
 
-    fn from_info_bytes(buffer: &[u8]) -> Result<(Self, usize), BundlrError> {
-        let sig_type_b = &buffer[0..2];
+    /// Same as [`BundlrTx::new`], but with tags already Avro encoded, e.g. taken from a parsed
+    /// item. `encoded_tags` is hashed and serialized as is, so a re-bundled item keeps its id
+    /// even if re-encoding its tags wouldn't give back the same bytes. Fails with
+    /// `BundlrError::InvalidTagEncoding` if `encoded_tags` doesn't decode to `tag_count` tags.
+    pub fn new_with_encoded_tags(
+        target: Vec<u8>,
+        data: Vec<u8>,
+        encoded_tags: Bytes,
+        tag_count: u64,
+    ) -> Result<Self, BundlrError> {
+        let tags = if encoded_tags.is_empty() {
+            vec![]
+        } else {
+            encoded_tags.to_vec().as_mut_slice().decode()?
+        };
+        if tags.len() as u64 != tag_count {
+            return Err(BundlrError::InvalidTagEncoding);
+        }
+        Ok(BundlrTx {
+            encoded_tags: Some(encoded_tags),
+            ..BundlrTx::new(target, data, tags)?
+        })
+    }
+
+    fn parse_fields<'a>(
+        buffer: &'a [u8],
+        limits: &TagLimits,
+    ) -> Result<ItemFields<'a>, BundlrError> {
+        let slice =
+            |start: usize, end: usize| buffer.get(start..end).ok_or(BundlrError::NoBytesLeft);
+
+        let sig_type_b = slice(0, 2)?;
         let signature_type = u16::from_le_bytes(
             <[u8; 2]>::try_from(sig_type_b)
                 .map_err(|err| BundlrError::BytesError(err.to_string()))?,
@@ -64,72 +140,153 @@ impl BundlrTx {
             pub_length,
             sig_length,
             ..
-        } = signer.get_config();
+        } = signer.get_config()?;
 
-        let signature = &buffer[2..2 + sig_length];
-        let owner = &buffer[2 + sig_length..2 + sig_length + pub_length];
+        // A truncated signature or owner can't have the length of the signature type
+        let signature = slice(2, 2 + sig_length).map_err(|_| BundlrError::InvalidSignature)?;
+        let owner = slice(2 + sig_length, 2 + sig_length + pub_length).map_err(|_| {
+            BundlrError::InvalidKey(format!("Owner should be {} bytes long", pub_length))
+        })?;
 
         let target_start = 2 + sig_length + pub_length;
         let target_present = u8::from_le_bytes(
-            <[u8; 1]>::try_from(&buffer[target_start..target_start + 1])
+            <[u8; 1]>::try_from(slice(target_start, target_start + 1)?)
                 .map_err(|err| BundlrError::BytesError(err.to_string()))?,
         );
         let target = match target_present {
             0 => &[],
-            1 => &buffer[target_start + 1..target_start + 33],
+            1 => slice(target_start + 1, target_start + 33)?,
             b => return Err(BundlrError::InvalidPresenceByte(b.to_string())),
         };
         let anchor_start = target_start + 1 + target.len();
         let anchor_present = u8::from_le_bytes(
-            <[u8; 1]>::try_from(&buffer[anchor_start..anchor_start + 1])
+            <[u8; 1]>::try_from(slice(anchor_start, anchor_start + 1)?)
                 .map_err(|err| BundlrError::BytesError(err.to_string()))?,
         );
         let anchor = match anchor_present {
             0 => &[],
-            1 => &buffer[anchor_start + 1..anchor_start + 33],
+            1 => slice(anchor_start + 1, anchor_start + 33)?,
             b => return Err(BundlrError::InvalidPresenceByte(b.to_string())),
         };
 
         let tags_start = anchor_start + 1 + anchor.len();
         let number_of_tags = u64::from_le_bytes(
-            <[u8; 8]>::try_from(&buffer[tags_start..tags_start + 8])
+            <[u8; 8]>::try_from(slice(tags_start, tags_start + 8)?)
                 .map_err(|err| BundlrError::BytesError(err.to_string()))?,
         );
 
         let number_of_tags_bytes = u64::from_le_bytes(
-            <[u8; 8]>::try_from(&buffer[tags_start + 8..tags_start + 16])
+            <[u8; 8]>::try_from(slice(tags_start + 8, tags_start + 16)?)
                 .map_err(|err| BundlrError::BytesError(err.to_string()))?,
         );
+        if number_of_tags > limits.max_tags || number_of_tags_bytes > limits.max_tags_bytes {
+            return Err(BundlrError::InvalidTagEncoding);
+        }
+        let data_start = usize::try_from(number_of_tags_bytes)
+            .ok()
+            .and_then(|tags_bytes| (tags_start + 16).checked_add(tags_bytes))
+            .filter(|data_start| *data_start <= buffer.len())
+            .ok_or(BundlrError::InvalidTagEncoding)?;
 
-        let mut b = buffer.to_vec();
-        let mut tags_bytes =
-            &mut b[tags_start + 16..tags_start + 16 + number_of_tags_bytes as usize];
+        Ok(ItemFields {
+            signature_type: signer,
+            signature,
+            owner,
+            target,
+            anchor,
+            number_of_tags,
+            tags: slice(tags_start + 16, data_start)?,
+            data_start,
+        })
+    }
+
+    /// Decodes the tags of a parsed item, failing with `BundlrError::InvalidTagEncoding` if they
+    /// don't match the declared number of tags
+    fn decode_tags(fields: &ItemFields) -> Result<Vec<Tag>, BundlrError> {
+        let mut b = fields.tags.to_vec();
+        let mut tags_bytes = &mut b[..];
 
-        let tags = if number_of_tags_bytes > 0 {
+        let tags = if !fields.tags.is_empty() {
             tags_bytes.decode()?
         } else {
             vec![]
         };
 
-        if number_of_tags != tags.len() as u64 {
+        if fields.number_of_tags != tags.len() as u64 {
             return Err(BundlrError::InvalidTagEncoding);
         }
+        Ok(tags)
+    }
+
+    fn from_info_bytes(buffer: &[u8], limits: &TagLimits) -> Result<(Self, usize), BundlrError> {
+        let fields = BundlrTx::parse_fields(buffer, limits)?;
+        let tags = BundlrTx::decode_tags(&fields)?;
 
         let bundlr_tx = BundlrTx {
-            signature_type: signer,
-            signature: signature.to_vec(),
-            owner: owner.to_vec(),
-            target: target.to_vec(),
-            anchor: anchor.to_vec(),
+            signature_type: fields.signature_type,
+            signature: fields.signature.to_vec(),
+            owner: fields.owner.to_vec(),
+            target: fields.target.to_vec(),
+            anchor: fields.anchor.to_vec(),
             tags,
+            encoded_tags: (!fields.tags.is_empty()).then(|| Bytes::copy_from_slice(fields.tags)),
             data: Data::None,
+            message: None,
         };
 
-        Ok((bundlr_tx, tags_start + 16 + number_of_tags_bytes as usize))
+        Ok((bundlr_tx, fields.data_start))
+    }
+
+    /// Verifies a whole serialized item held in memory without building a `BundlrTx`: the
+    /// deep hash message is computed straight from `buffer`, and tags are hashed as encoded
+    /// once checked to decode as [`BundlrTx::from_bytes`] would. Meant for small items, where
+    /// copying the fields costs more than hashing them. Returns the item signature.
+    pub fn verify_bytes(buffer: &[u8]) -> Result<&[u8], BundlrError> {
+        let fields = BundlrTx::parse_fields(buffer, &TagLimits::default())?;
+        BundlrTx::decode_tags(&fields)?;
+        let sig_type = fields.signature_type.as_u16().to_string();
+        let message = deep_hash_blobs(&[
+            DATAITEM_AS_BUFFER,
+            ONE_AS_BUFFER,
+            sig_type.as_bytes(),
+            fields.owner,
+            fields.target,
+            fields.anchor,
+            fields.tags,
+            &buffer[fields.data_start..],
+        ]);
+        fields
+            .signature_type
+            .verify(fields.owner, &message, fields.signature)?;
+
+        Ok(fields.signature)
+    }
+
+    /// Reads the signature type of a serialized item from its first two bytes, without parsing
+    /// the rest of the item. Unknown signature types are an error.
+    pub fn peek_signer_type(bytes: &[u8]) -> Result<SignerMap, BundlrError> {
+        match bytes {
+            [b0, b1, ..] => SignerMap::try_from_u16(u16::from_le_bytes([*b0, *b1])),
+            _ => Err(BundlrError::NoBytesLeft),
+        }
+    }
+
+    /// Parses the fields of a serialized item without its data, e.g. when the data is fetched
+    /// separately. `buffer` may end at the start of the data. See [`BundlrTx::verify_with_data`].
+    pub fn from_header_bytes(buffer: &[u8]) -> Result<Self, BundlrError> {
+        BundlrTx::from_info_bytes(buffer, &TagLimits::default()).map(|(bundlr_tx, _)| bundlr_tx)
     }
 
     pub fn from_bytes(buffer: Vec<u8>) -> Result<Self, BundlrError> {
-        let (bundlr_tx, data_start) = BundlrTx::from_info_bytes(&buffer)?;
+        BundlrTx::from_bytes_with_limits(buffer, &TagLimits::default())
+    }
+
+    /// Same as [`BundlrTx::from_bytes`], but with custom bounds on the tags of the item
+    pub fn from_bytes_with_limits(
+        buffer: Vec<u8>,
+        limits: &TagLimits,
+    ) -> Result<Self, BundlrError> {
+        let (bundlr_tx, data_start) = BundlrTx::from_info_bytes(&buffer, limits)?;
         let data = &buffer[data_start..buffer.len()];
 
         Ok(BundlrTx {
@@ -144,62 +301,258 @@ impl BundlrTx {
         offset: u64,
         length: usize,
     ) -> Result<Self, BundlrError> {
+        BundlrTx::from_file_position_with_chunk_size(file, size, offset, length, CHUNK_SIZE)
+    }
+
+    /// Same as [`BundlrTx::from_file_position`], but streams the item's data in chunks of
+    /// `chunk_size` bytes. Bigger chunks lower the per-chunk overhead on fast disks, smaller ones
+    /// reduce memory usage. Fails with `BundlrError::InvalidChunkSize` if `chunk_size` is 0.
+    pub fn from_file_position_with_chunk_size(
+        file: &mut File,
+        size: u64,
+        offset: u64,
+        length: usize,
+        chunk_size: u64,
+    ) -> Result<Self, BundlrError> {
+        if chunk_size == 0 {
+            return Err(BundlrError::InvalidChunkSize(chunk_size));
+        }
+
         let buffer = read_offset(file, offset, length).map_err(BundlrError::IoError)?;
-        let (bundlr_tx, data_start) = BundlrTx::from_info_bytes(&buffer)?;
+        let (bundlr_tx, data_start) = BundlrTx::from_info_bytes(&buffer, &TagLimits::default())?;
 
         let data_start = data_start as u64;
-        let data_size = size - data_start;
+        let data_size = size
+            .checked_sub(data_start)
+            .ok_or(BundlrError::NoBytesLeft)?;
         let mut file_clone = file.try_clone()?;
+        // The declared size is checked against what the file actually holds while streaming, so
+        // a corrupt header size fails verification instead of hashing wrong data
         let file_stream = try_stream! {
-            let chunk_size = CHUNK_SIZE;
             let mut read = 0;
             while read < data_size {
-                let b = read_offset(&mut file_clone, offset + data_start + read, cmp::min(data_size - read, chunk_size) as usize)?;
+                let expected = cmp::min(data_size - read, chunk_size);
+                let mut b = Vec::with_capacity(expected as usize);
+                file_clone.seek(SeekFrom::Start(offset + data_start + read))?;
+                (&mut file_clone).take(expected).read_to_end(&mut b)?;
+                if (b.len() as u64) < expected {
+                    Err(BundlrError::NoBytesLeft)?;
+                }
                 read += b.len() as u64;
-                yield b;
+                yield Bytes::from(b);
             };
         };
 
         Ok(BundlrTx {
-            data: Data::Stream(Box::pin(file_stream)),
+            data: Data::Stream(Box::pin(file_stream), data_size),
             ..bundlr_tx
         })
     }
 
+    /// Creates an unsigned item whose data is the `len` bytes of `mmap` from `offset`. The data
+    /// is streamed in chunks copied straight out of the mapping, so large files are neither read
+    /// into memory up front nor read with a syscall per chunk.
+    ///
+    /// The mapping is shared with the data stream, which outlives this call, hence the `Arc`.
+    /// The mapped file must not be modified while the item is in use and, as with
+    /// [`BundlrTx::from_file_position`], the data can only be streamed once.
+    #[cfg(feature = "memmap")]
+    pub fn from_mmap(
+        mmap: Arc<memmap2::Mmap>,
+        offset: usize,
+        len: usize,
+        tags: Vec<Tag>,
+        target: Vec<u8>,
+    ) -> Result<Self, BundlrError> {
+        match offset.checked_add(len) {
+            Some(end) if end <= mmap.len() => (),
+            _ => return Err(BundlrError::NoBytesLeft),
+        };
+
+        let chunk_size = CHUNK_SIZE as usize;
+        let chunks = (0..len).step_by(chunk_size).map(move |start| {
+            let end = offset + cmp::min(start + chunk_size, len);
+            Ok(Bytes::copy_from_slice(&mmap[offset + start..end]))
+        });
+
+        Ok(BundlrTx {
+            data: Data::Stream(Box::pin(stream::iter(chunks)), len as u64),
+            ..BundlrTx::new(target, vec![], tags)?
+        })
+    }
+
+    /// Replaces the randomly generated anchor. The anchor has to be either empty or 32 bytes long.
+    pub fn set_anchor(&mut self, anchor: Vec<u8>) -> Result<(), BundlrError> {
+        if !anchor.is_empty() && anchor.len() != 32 {
+            return Err(BundlrError::BytesError(format!(
+                "Anchor should be 32 bytes long, got {}",
+                anchor.len()
+            )));
+        }
+        self.anchor = anchor;
+        self.message = None;
+        Ok(())
+    }
+
+    /// Sets the anchor to the id of a previous item, given in base64url, so that chained items
+    /// reference their predecessor. The id has to decode to 32 bytes.
+    pub fn with_anchor_tx(&mut self, prev_id: &str) -> Result<(), BundlrError> {
+        let anchor = BASE64URL_NOPAD
+            .decode(prev_id.trim_end_matches('=').as_bytes())
+            .map_err(|err| BundlrError::Base64Error(err.to_string()))?;
+        if anchor.len() != 32 {
+            return Err(BundlrError::BytesError(format!(
+                "Anchor should be 32 bytes long, got {}",
+                anchor.len()
+            )));
+        }
+        self.set_anchor(anchor)
+    }
+
+    /// Sets the target from its base64url representation, which has to decode to 32 bytes.
+    pub fn with_target_address(&mut self, addr: &str) -> Result<(), BundlrError> {
+        let target = BASE64URL_NOPAD
+            .decode(addr.trim_end_matches('=').as_bytes())
+            .map_err(|err| BundlrError::Base64Error(err.to_string()))?;
+        if target.len() != 32 {
+            return Err(BundlrError::BytesError(format!(
+                "Target should be 32 bytes long, got {}",
+                target.len()
+            )));
+        }
+        self.target = target;
+        self.message = None;
+        Ok(())
+    }
+
+    /// Removes duplicates of the `singletons` tag names, compared case-insensitively, keeping
+    /// only their last occurrence. Other tags are left untouched and in order.
+    ///
+    /// Must be called before signing, as changing the tags invalidates the signature.
+    pub fn normalize_tags(&mut self, singletons: &[&str]) {
+        let mut seen = HashSet::new();
+        let tag_count = self.tags.len();
+        let mut tags = Vec::with_capacity(tag_count);
+        for tag in self.tags.drain(..).rev() {
+            let name = tag.name.to_ascii_lowercase();
+            let is_singleton = singletons.iter().any(|s| s.eq_ignore_ascii_case(&name));
+            if !is_singleton || seen.insert(name) {
+                tags.push(tag);
+            }
+        }
+        tags.reverse();
+        if tags.len() != tag_count {
+            self.encoded_tags = None;
+        }
+        self.tags = tags;
+        self.message = None;
+    }
+
+    /// Stamps the item with the `App-Name` and `App-Version` tags, replacing any previous ones.
+    ///
+    /// Must be called before signing, as changing the tags invalidates the signature.
+    pub fn set_app(&mut self, name: &str, version: &str) {
+        self.tags.retain(|tag| {
+            !tag.name.eq_ignore_ascii_case(APP_NAME_TAG)
+                && !tag.name.eq_ignore_ascii_case(APP_VERSION_TAG)
+        });
+        self.tags.push(Tag::new(APP_NAME_TAG, name));
+        self.tags.push(Tag::new(APP_VERSION_TAG, version));
+        self.encoded_tags = None;
+        self.message = None;
+    }
+
+    /// The `App-Name` and `App-Version` tag values, if both are present
+    pub fn app(&self) -> Option<(String, String)> {
+        let find = |name: &str| {
+            self.tags
+                .iter()
+                .rev()
+                .find(|tag| tag.name.eq_ignore_ascii_case(name))
+                .map(|tag| tag.value.clone())
+        };
+        Some((find(APP_NAME_TAG)?, find(APP_VERSION_TAG)?))
+    }
+
+    /// Checks the item `Deadline-Height` tag, if any, against `current_height`. Items past their
+    /// deadline are rejected with [`BundlrError::Expired`].
+    pub fn verify_not_expired(&self, current_height: u64) -> Result<(), BundlrError> {
+        let deadline = match self
+            .tags
+            .iter()
+            .find(|tag| tag.name.eq_ignore_ascii_case(DEADLINE_HEIGHT_TAG))
+        {
+            Some(tag) => tag
+                .value
+                .parse::<u64>()
+                .map_err(|err| BundlrError::ParseError(err.to_string()))?,
+            None => return Ok(()),
+        };
+        if current_height > deadline {
+            return Err(BundlrError::Expired(deadline));
+        }
+        Ok(())
+    }
+
+    /// The signed receipt requested by the item, if its `Receipt-Request` tag is `true`, along
+    /// with the deadline of its `Deadline-Height` tag. A deadline that isn't a number is ignored.
+    pub fn receipt_request(&self) -> Option<ReceiptRequest> {
+        let find = |name: &str| {
+            self.tags
+                .iter()
+                .find(|tag| tag.name.eq_ignore_ascii_case(name))
+                .map(|tag| tag.value.as_str())
+        };
+        if !find(RECEIPT_REQUEST_TAG)?.eq_ignore_ascii_case("true") {
+            return None;
+        }
+        Some(ReceiptRequest {
+            deadline_height: find(DEADLINE_HEIGHT_TAG).and_then(|value| value.parse().ok()),
+        })
+    }
+
+    /// Whether both items carry the same target, tags and data, regardless of their anchor,
+    /// owner and signature. Data streamed from a file can't be compared, so items with streamed
+    /// or unloaded data are never content-equal.
+    pub fn content_eq(&self, other: &BundlrTx) -> bool {
+        let same_data = match (&self.data, &other.data) {
+            (Data::Bytes(data), Data::Bytes(other_data)) => data == other_data,
+            _ => false,
+        };
+        same_data && self.target == other.target && self.tags == other.tags
+    }
+
     pub fn is_signed(&self) -> bool {
         !self.signature.is_empty() && self.signature_type != SignerMap::None
     }
 
-    pub fn as_bytes(self) -> Result<Vec<u8>, BundlrError> {
+    /// Avro encoding of the tags, empty when there are none
+    fn encoded_tags(&self) -> Result<Bytes, BundlrError> {
+        match &self.encoded_tags {
+            Some(encoded_tags) => Ok(encoded_tags.clone()),
+            None if self.tags.is_empty() => Ok(Bytes::default()),
+            None => self.tags.encode(),
+        }
+    }
+
+    /// Serializes everything but the data, reserving room for `data_len` more bytes
+    fn info_bytes(&self, data_len: usize) -> Result<Vec<u8>, BundlrError> {
         if !self.is_signed() {
             return Err(BundlrError::NoSignature);
         }
-        let data = match &self.data {
-            Data::Stream(_) => return Err(BundlrError::InvalidDataType),
-            Data::None => return Err(BundlrError::InvalidDataType),
-            Data::Bytes(data) => data,
-        };
 
-        let encoded_tags = if !self.tags.is_empty() {
-            self.tags.encode()?
-        } else {
-            Bytes::default()
-        };
-        let config = self.signature_type.get_config();
-        let length = 2u64
-            + config.sig_length as u64
-            + config.pub_length as u64
-            + 34
-            + 16
-            + encoded_tags.len() as u64
-            + data.len() as u64;
-
-        let mut b = Vec::with_capacity(
-            TryInto::<usize>::try_into(length)
-                .map_err(|err| BundlrError::TypeParseError(err.to_string()))?,
-        );
+        let encoded_tags = self.encoded_tags()?;
+        let length = serialized_length(&[
+            self.signature_type.min_item_size()? as u64,
+            self.target.len() as u64,
+            self.anchor.len() as u64,
+            encoded_tags.len() as u64,
+            data_len as u64,
+        ])?;
 
-        let sig_type: [u8; 2] = (self.signature_type as u16).to_le_bytes();
+        let mut b = Vec::with_capacity(length);
+
+        let sig_type: [u8; 2] = self.signature_type.as_u16().to_le_bytes();
         let target_presence_byte = if self.target.is_empty() {
             &[0u8]
         } else {
@@ -225,41 +578,129 @@ impl BundlrTx {
             b.put(encoded_tags);
         }
 
+        Ok(b)
+    }
+
+    /// Serialized signed item without its data: everything [`BundlrTx::as_bytes`] emits before
+    /// the data, for transports that send the header and the data separately
+    pub fn header_bytes(&self) -> Result<Vec<u8>, BundlrError> {
+        self.info_bytes(0)
+    }
+
+    pub fn as_bytes(self) -> Result<Vec<u8>, BundlrError> {
+        let data = match &self.data {
+            Data::Stream(..) => return Err(BundlrError::InvalidDataType),
+            Data::None => return Err(BundlrError::InvalidDataType),
+            Data::Bytes(data) => data,
+        };
+
+        let mut b = self.info_bytes(data.len())?;
         b.put(&data[..]);
         Ok(b)
     }
 
+    /// Size in bytes of the serialized item without its data, i.e. the offset of the data
+    pub(crate) fn header_size(&self) -> Result<u64, BundlrError> {
+        Ok(self.header_bytes()?.len() as u64)
+    }
+
+    /// Whether the item data is itself a binary bundle, as declared by its `Bundle-Format:
+    /// binary` and `Bundle-Version: 2.0.0` tags
+    pub fn is_bundle(&self) -> bool {
+        let tag_value = |name: &str| self.tags.iter().find(|tag| tag.name == name);
+        matches!(tag_value("Bundle-Format"), Some(tag) if tag.value == "binary")
+            && matches!(tag_value("Bundle-Version"), Some(tag) if tag.value == "2.0.0")
+    }
+
+    /// Size in bytes of the serialized item
+    pub fn size(&self) -> Result<u64, BundlrError> {
+        let data_len = match &self.data {
+            Data::None => return Err(BundlrError::InvalidDataType),
+            Data::Bytes(data) => data.len() as u64,
+            Data::Stream(_, len) => *len,
+        };
+        Ok(self.header_size()? + data_len)
+    }
+
+    /// Writes the serialized item to `writer`, streaming its data when it is backed by a file.
+    /// A data stream can only be read once, so it must not have been consumed (e.g. by
+    /// verifying the item) before.
+    pub async fn write_to<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<(), BundlrError> {
+        let data_len = match &self.data {
+            Data::None => return Err(BundlrError::InvalidDataType),
+            Data::Bytes(data) => data.len(),
+            Data::Stream(..) => 0,
+        };
+        writer.write_all(&self.info_bytes(data_len)?).await?;
+
+        match &mut self.data {
+            Data::None => return Err(BundlrError::InvalidDataType),
+            Data::Bytes(data) => writer.write_all(data).await?,
+            Data::Stream(stream, len) => {
+                let mut written = 0;
+                while let Some(chunk) = stream.next().await {
+                    let chunk = chunk.map_err(|err| BundlrError::FsError(err.to_string()))?;
+                    writer.write_all(&chunk).await?;
+                    written += chunk.len() as u64;
+                }
+                if written != *len {
+                    return Err(BundlrError::BytesError(format!(
+                        "Expected {} bytes of data, streamed {}",
+                        len, written
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialized signed item as a stream of its [`BundlrTx::header_bytes`] followed by its data,
+    /// which is streamed when it is backed by a file
     pub fn as_byte_stream(
         self,
     ) -> Result<Pin<Box<dyn Stream<Item = anyhow::Result<Bytes>>>>, BundlrError> {
-        todo!();
+        let header = stream::once(future::ready(Ok(Bytes::from(self.header_bytes()?))));
+        match self.data {
+            Data::None => Err(BundlrError::InvalidDataType),
+            Data::Bytes(data) => Ok(Box::pin(
+                header.chain(stream::once(future::ready(Ok(Bytes::from(data))))),
+            )),
+            Data::Stream(data, _) => Ok(Box::pin(header.chain(data))),
+        }
     }
 
     async fn get_message(&mut self) -> Result<Bytes, BundlrError> {
-        let encoded_tags = if !self.tags.is_empty() {
-            self.tags.encode()?
-        } else {
-            Bytes::default()
-        };
+        if let Some(message) = &self.message {
+            return Ok(message.clone());
+        }
+        let message = self.compute_message().await?;
+        self.message = Some(message.clone());
+        Ok(message)
+    }
+
+    async fn compute_message(&mut self) -> Result<Bytes, BundlrError> {
+        let encoded_tags = self.encoded_tags()?;
 
         match &mut self.data {
             Data::None => Ok(Bytes::new()),
             Data::Bytes(data) => {
-                let data_chunk = DeepHashChunk::Chunk(data.clone().into());
-                let sig_type = &self.signature_type;
-                let sig_type_bytes = sig_type.as_u16().to_string().as_bytes().to_vec();
-                deep_hash_sync(DeepHashChunk::Chunks(vec![
-                    DeepHashChunk::Chunk(DATAITEM_AS_BUFFER.into()),
-                    DeepHashChunk::Chunk(ONE_AS_BUFFER.into()),
-                    DeepHashChunk::Chunk(sig_type_bytes.to_vec().into()),
-                    DeepHashChunk::Chunk(self.owner.to_vec().into()),
-                    DeepHashChunk::Chunk(self.target.to_vec().into()),
-                    DeepHashChunk::Chunk(self.anchor.to_vec().into()),
-                    DeepHashChunk::Chunk(encoded_tags.clone()),
-                    data_chunk,
+                // Hashed from the borrowed fields, a `DeepHashChunk` would copy the whole data
+                let sig_type = self.signature_type.as_u16().to_string();
+                Ok(deep_hash_blobs(&[
+                    DATAITEM_AS_BUFFER,
+                    ONE_AS_BUFFER,
+                    sig_type.as_bytes(),
+                    &self.owner,
+                    &self.target,
+                    &self.anchor,
+                    &encoded_tags,
+                    data,
                 ]))
             }
-            Data::Stream(file_stream) => {
+            Data::Stream(file_stream, _) => {
                 let data_chunk = DeepHashChunk::Stream(file_stream);
                 let sig_type = &self.signature_type;
                 let sig_type_bytes = sig_type.as_u16().to_string().as_bytes().to_vec();
@@ -279,8 +720,13 @@ impl BundlrTx {
     }
 
     pub async fn sign(&mut self, signer: &dyn Signer) -> Result<(), BundlrError> {
-        self.signature_type = signer.sig_type();
-        self.owner = signer.pub_key().to_vec();
+        let sig_type = signer.sig_type();
+        let owner = signer.pub_key().to_vec();
+        if sig_type != self.signature_type || owner != self.owner {
+            self.message = None;
+        }
+        self.signature_type = sig_type;
+        self.owner = owner;
 
         let message = self.get_message().await?;
 
@@ -290,7 +736,32 @@ impl BundlrTx {
         Ok(())
     }
 
+    /// Checks that the signature and owner have the lengths of the signature type, failing with
+    /// [`BundlrError::InvalidSignature`] or [`BundlrError::InvalidKey`]. Parsed items always
+    /// do, but items signed by a faulty [`Signer`] may not.
+    pub fn check_lengths(&self) -> Result<(), BundlrError> {
+        let Config {
+            sig_length,
+            pub_length,
+            ..
+        } = self.signature_type.get_config()?;
+        if self.signature.len() != sig_length {
+            return Err(BundlrError::InvalidSignature);
+        }
+        if self.owner.len() != pub_length {
+            return Err(BundlrError::InvalidKey(format!(
+                "Owner should be {} bytes long, got {}",
+                pub_length,
+                self.owner.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Verifies the signature of the item against its owner, after checking their lengths with
+    /// [`BundlrTx::check_lengths`]
     pub async fn verify(&mut self) -> Result<(), BundlrError> {
+        self.check_lengths()?;
         let message = self.get_message().await?;
         let pub_key = &self.owner;
         let signature = &self.signature;
@@ -299,18 +770,130 @@ impl BundlrTx {
         verifier.verify(pub_key, &message, signature)
     }
 
+    /// Same as [`BundlrTx::verify`], but checks the signature against `owner` instead of the owner
+    /// embedded in the item, for schemes where the embedded owner is not the verifying key.
+    /// The signed message still covers the embedded owner.
+    pub async fn verify_with_owner(&mut self, owner: &[u8]) -> Result<(), BundlrError> {
+        self.check_lengths()?;
+        let message = self.get_message().await?;
+        self.signature_type.verify(owner, &message, &self.signature)
+    }
+
+    /// Same as [`BundlrTx::verify`], but checks the signature of injected Aptos items against
+    /// `nonce` instead of the default one, for items signed with
+    /// [`crate::AptosSigner::with_nonce`]. The nonce is not part of the item, so verifiers must
+    /// know it beforehand.
+    #[cfg(feature = "aptos")]
+    pub async fn verify_with_aptos_nonce(&mut self, nonce: &str) -> Result<(), BundlrError> {
+        if self.signature_type != SignerMap::InjectedAptos {
+            return self.verify().await;
+        }
+        self.check_lengths()?;
+        let message = self.get_message().await?;
+        crate::signers::aptos::verify_aptos_message(
+            Bytes::copy_from_slice(&self.owner),
+            message,
+            Bytes::copy_from_slice(&self.signature),
+            nonce,
+        )
+    }
+
+    /// Sets the item data to `data`, replacing any loaded data, and verifies the item signature
+    /// covers it. Meant for items parsed with [`BundlrTx::from_header_bytes`].
+    pub async fn verify_with_data(&mut self, data: &[u8]) -> Result<(), BundlrError> {
+        self.data = Data::Bytes(data.to_vec());
+        self.message = None;
+        self.verify().await
+    }
+
     pub fn get_signarure(&self) -> Vec<u8> {
         self.signature.clone()
     }
+
+    /// Splits the signature of an Ethereum item into its `r`, `s` and `v` components
+    pub fn signature_rsv(&self) -> Result<([u8; 32], [u8; 32], u8), BundlrError> {
+        match self.signature_type {
+            SignerMap::Ethereum | SignerMap::TypedEthereum => {}
+            _ => return Err(BundlrError::InvalidSignerType),
+        }
+        if self.signature.len() != 65 {
+            return Err(BundlrError::InvalidSignature);
+        }
+
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&self.signature[..32]);
+        s.copy_from_slice(&self.signature[32..64]);
+        Ok((r, s, self.signature[64]))
+    }
+
+    /// Consumes the item, returning its data. Fails with `BundlrError::InvalidDataType` if the
+    /// data is streamed from a file or not loaded, see [`BundlrTx::data_stream`].
+    pub fn into_data(self) -> Result<Bytes, BundlrError> {
+        match self.data {
+            Data::Bytes(data) => Ok(data.into()),
+            Data::None | Data::Stream(..) => Err(BundlrError::InvalidDataType),
+        }
+    }
+
+    /// Consumes the item, returning its data as a stream along with its length in bytes. Data
+    /// held in memory is returned as a single chunk.
+    #[allow(clippy::type_complexity)]
+    pub fn data_stream(
+        self,
+    ) -> Result<(Pin<Box<dyn Stream<Item = anyhow::Result<Bytes>>>>, u64), BundlrError> {
+        match self.data {
+            Data::Stream(stream, len) => Ok((stream, len)),
+            Data::Bytes(data) => {
+                let len = data.len() as u64;
+                Ok((
+                    Box::pin(futures::stream::once(async { Ok(data.into()) })),
+                    len,
+                ))
+            }
+            Data::None => Err(BundlrError::InvalidDataType),
+        }
+    }
+
+    pub fn get_tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    pub fn get_owner(&self) -> &[u8] {
+        &self.owner
+    }
+
+    /// Arweave style address of the owner, i.e. the base64url SHA-256 of its public key. This is
+    /// the owner address gateways report for items of any signature type.
+    pub fn owner_address(&self) -> String {
+        BASE64URL_NOPAD.encode(&Sha256::digest(&self.owner))
+    }
+
+    pub fn get_anchor(&self) -> &[u8] {
+        &self.anchor
+    }
+
+    pub fn get_target(&self) -> &[u8] {
+        &self.target
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tags::Tag;
+    use super::serialized_length;
+    use crate::error::BundlrError;
+    use crate::receipt::ReceiptRequest;
+    use crate::tags::{AvroDecode, AvroEncode, Tag, TagLimits};
+    use crate::test_support::{make_item, temp_path, test_signer, TEST_KEYPAIR};
     #[cfg(feature = "solana")]
     use crate::transaction::bundlr::BundlrTx;
-    use crate::{ArweaveSigner, Ed25519Signer, Secp256k1Signer};
+    use crate::transaction::rng::SeededRng;
+    use crate::{ArweaveSigner, Ed25519Signer, Secp256k1Signer, Signer};
+    use bytes::Bytes;
+    use data_encoding::BASE64URL_NOPAD;
+    use futures::TryStreamExt;
     use secp256k1::SecretKey;
+    use sha2::{Digest, Sha256};
     use std::path::PathBuf;
     use std::str::FromStr;
     use std::{fs, fs::File, io::Write};
@@ -321,11 +904,83 @@ mod tests {
             tokio_test::block_on($e)
         };
     }
+    #[tokio::test]
+    async fn test_keep_non_canonical_tag_encoding() {
+        let signer = test_signer();
+        let tags = vec![Tag::new("b", "1"), Tag::new("a", "2")];
+
+        // Same tags as two Avro blocks of one tag each, a valid but different encoding
+        let first = vec![tags[0].clone()].encode().unwrap();
+        let second = vec![tags[1].clone()].encode().unwrap();
+        let split = [&first[..first.len() - 1], &second[..]].concat();
+        let mut split_tags = &mut split.clone()[..];
+        assert_eq!(split_tags.decode().unwrap(), tags);
+        assert_ne!(&split[..], &tags.encode().unwrap()[..]);
+
+        let mut item =
+            BundlrTx::new_with_encoded_tags(vec![], b"hello".to_vec(), split.into(), 2).unwrap();
+        item.sign(&signer).await.unwrap();
+        let bytes = item.as_bytes().unwrap();
+
+        // Parsed tags are hashed and serialized as read, not encoded again
+        let mut parsed = BundlrTx::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(parsed.get_tags(), &tags);
+        parsed.verify().await.unwrap();
+        assert_eq!(parsed.as_bytes().unwrap(), bytes);
+    }
+
+    #[cfg(feature = "aptos")]
+    #[tokio::test]
+    async fn test_verify_with_aptos_nonce() {
+        let signer = crate::AptosSigner::from_base58(TEST_KEYPAIR)
+            .unwrap()
+            .with_nonce("irys");
+        let mut item = BundlrTx::new(vec![], b"hello".to_vec(), vec![]).unwrap();
+        item.sign(&signer).await.unwrap();
+
+        assert!(item.verify().await.is_err());
+        assert!(item.verify_with_aptos_nonce("other").await.is_err());
+        item.verify_with_aptos_nonce("irys").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_new_with_encoded_tags() -> Result<(), BundlrError> {
+        let signer = test_signer();
+        let tags = vec![Tag::new("b", "1"), Tag::new("a", "2")];
+        let bytes = make_item(&signer, b"hello", &tags).await?;
+
+        // Ed25519 signatures are deterministic, so rebuilding the item gives back its bytes
+        let parsed = BundlrTx::from_bytes(bytes.clone())?;
+        let mut rebuilt =
+            BundlrTx::new_with_encoded_tags(vec![], b"hello".to_vec(), tags.encode()?, 2)?;
+        rebuilt.set_anchor(parsed.get_anchor().to_vec())?;
+        rebuilt.sign(&signer).await?;
+        assert_eq!(rebuilt.as_bytes()?, bytes);
+
+        // Same tags as two Avro blocks of one tag each, serialized verbatim
+        let first = vec![tags[0].clone()].encode()?;
+        let second = vec![tags[1].clone()].encode()?;
+        let split = Bytes::from([&first[..first.len() - 1], &second[..]].concat());
+        let mut item =
+            BundlrTx::new_with_encoded_tags(vec![], b"hello".to_vec(), split.clone(), 2)?;
+        assert_eq!(item.get_tags(), &tags);
+        item.sign(&signer).await?;
+        item.verify().await?;
+        let bytes = item.as_bytes()?;
+        let tags_end = bytes.len() - b"hello".len();
+        assert_eq!(&bytes[tags_end - split.len()..tags_end], &split[..]);
+
+        assert!(matches!(
+            BundlrTx::new_with_encoded_tags(vec![], vec![], split, 3),
+            Err(BundlrError::InvalidTagEncoding)
+        ));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_create_sign_verify_load_ed25519() {
-        let path = "./res/test_bundles/test_data_item_ed25519";
-        let secret_key = "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
-        let signer = Ed25519Signer::from_base58(secret_key).unwrap();
+        let path = &temp_path("test_data_item_ed25519");
+        let signer = test_signer();
         let mut data_item_1 = BundlrTx::new(
             Vec::from(""),
             Vec::from("hello"),
@@ -348,7 +1003,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_create_sign_verify_load_rsa4096() {
-        let path = "./res/test_bundles/test_data_item_rsa4096";
+        let path = &temp_path("test_data_item_rsa4096");
         let key_path = PathBuf::from_str("res/test_wallet.json").unwrap();
         let signer = ArweaveSigner::from_keypair_path(key_path).unwrap();
         let mut data_item_1 = BundlrTx::new(
@@ -374,7 +1029,7 @@ mod tests {
     async fn test_create_sign_verify_load_cosmos() {
         //TODO: assign cosmos constant then fix this
         /*
-        let path = "./res/test_bundles/test_data_item_cosmos";
+        let path = &temp_path("test_data_item_cosmos");
         let base58_secret_key = "28PmkjeZqLyfRQogb3FU4E1vJh68dXpbojvS2tcPwezZmVQp8zs8ebGmYg1hNRcjX4DkUALf3SkZtytGWPG3vYhs";
         let signer = CosmosSigner::from_base58(base58_secret_key).unwrap();
         let mut data_item_1 = BundlrTx::new(
@@ -396,9 +1051,135 @@ mod tests {
          */
     }
 
+    #[cfg(all(feature = "memmap", feature = "solana"))]
+    #[tokio::test]
+    async fn test_from_mmap() -> Result<(), BundlrError> {
+        let path = &temp_path("test_mmap_data");
+        let data = (0..crate::consts::CHUNK_SIZE as usize * 2 + 10)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        std::fs::write(path, &data)?;
+        let mmap = std::sync::Arc::new(unsafe { memmap2::Mmap::map(&File::open(path)?)? });
+
+        let signer = test_signer();
+        let tags = vec![Tag::new("name", "value")];
+        let mut mapped =
+            BundlrTx::from_mmap(mmap.clone(), 5, data.len() - 5, tags.clone(), vec![])?;
+        let mut in_memory = BundlrTx::new(vec![], data[5..].to_vec(), tags)?;
+        in_memory.set_anchor(mapped.get_anchor().to_vec())?;
+        mapped.sign(&signer).await?;
+        in_memory.sign(&signer).await?;
+
+        assert_eq!(mapped.get_signarure(), in_memory.get_signarure());
+        assert_eq!(mapped.size()?, in_memory.size()?);
+        assert!(matches!(
+            BundlrTx::from_mmap(mmap, 6, data.len() - 5, vec![], vec![]),
+            Err(BundlrError::NoBytesLeft)
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "sui")]
+    #[tokio::test]
+    async fn test_create_sign_verify_load_sui() {
+        use crate::SuiSigner;
+
+        let path = &temp_path("test_data_item_sui");
+        let signer = SuiSigner::from_base58(TEST_KEYPAIR).unwrap();
+        let mut data_item_1 = BundlrTx::new(
+            Vec::from(""),
+            Vec::from("hello"),
+            vec![Tag::new("name", "value")],
+        )
+        .unwrap();
+        let res = data_item_1.sign(&signer).await;
+        assert!(res.is_ok());
+
+        let mut f = File::create(path).unwrap();
+        let data_item_1_bytes = data_item_1.as_bytes().unwrap();
+        f.write_all(&data_item_1_bytes).unwrap();
+
+        let buffer = fs::read(path).expect("Could not read file");
+        let mut data_item_2 = BundlrTx::from_bytes(buffer).expect("Invalid bytes");
+        assert!(&data_item_2.is_signed());
+        assert!(data_item_2.verify().await.is_ok());
+        assert_eq!(data_item_1_bytes, data_item_2.as_bytes().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_bytes() {
+        let signer = test_signer();
+        let mut data_item = BundlrTx::new(
+            Vec::from(""),
+            Vec::from("hello"),
+            vec![Tag::new("name", "value"), Tag::new("other", "tag")],
+        )
+        .unwrap();
+        data_item.sign(&signer).await.unwrap();
+        let signature = data_item.get_signarure();
+        let mut bytes = data_item.as_bytes().unwrap();
+
+        assert_eq!(BundlrTx::verify_bytes(&bytes).unwrap(), &signature[..]);
+
+        // The tag count isn't signed, but must still match the encoded tags
+        let mut miscounted = bytes.clone();
+        miscounted[132..140].copy_from_slice(&3u64.to_le_bytes());
+        assert!(matches!(
+            BundlrTx::verify_bytes(&miscounted),
+            Err(BundlrError::InvalidTagEncoding)
+        ));
+
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(BundlrTx::verify_bytes(&bytes).is_err());
+        assert!(BundlrTx::verify_bytes(&bytes[..bytes.len() - 6]).is_err());
+        assert!(BundlrTx::verify_bytes(&bytes[..120]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_verify_custom_chunk_size() {
+        let path = &temp_path("test_data_item_chunk_size");
+        let signer = test_signer();
+        let mut data_item = BundlrTx::new(
+            Vec::from(""),
+            vec![7u8; 100_000],
+            vec![Tag::new("name", "value")],
+        )
+        .unwrap();
+        data_item.sign(&signer).await.unwrap();
+
+        let data_item_bytes = data_item.as_bytes().unwrap();
+        fs::write(path, &data_item_bytes).unwrap();
+
+        for chunk_size in [1024, 4096, 1024 * 1024] {
+            let mut file = File::open(path).unwrap();
+            let mut data_item = BundlrTx::from_file_position_with_chunk_size(
+                &mut file,
+                data_item_bytes.len() as u64,
+                0,
+                4096,
+                chunk_size,
+            )
+            .unwrap();
+            assert!(data_item.verify().await.is_ok());
+        }
+
+        let mut file = File::open(path).unwrap();
+        assert!(matches!(
+            BundlrTx::from_file_position_with_chunk_size(
+                &mut file,
+                data_item_bytes.len() as u64,
+                0,
+                4096,
+                0
+            ),
+            Err(BundlrError::InvalidChunkSize(0))
+        ));
+    }
+
     #[tokio::test]
     async fn test_create_sign_verify_load_secp256k1() {
-        let path = "./res/test_bundles/test_data_item_secp256k1";
+        let path = &temp_path("test_data_item_secp256k1");
         let secret_key = SecretKey::from_slice(b"00000000000000000000000000000000").unwrap();
         let signer = Secp256k1Signer::new(secret_key);
         let mut data_item_1 = BundlrTx::new(
@@ -418,4 +1199,502 @@ mod tests {
         assert!(&data_item_2.is_signed());
         assert_eq!(data_item_1_bytes, data_item_2.as_bytes().unwrap());
     }
+
+    #[tokio::test]
+    async fn test_signature_rsv() {
+        let secret_key = SecretKey::from_slice(b"00000000000000000000000000000000").unwrap();
+        let signer = Secp256k1Signer::new(secret_key);
+        let mut data_item = BundlrTx::new(vec![], Vec::from("hello"), vec![]).unwrap();
+        data_item.sign(&signer).await.unwrap();
+
+        let (r, s, v) = data_item.signature_rsv().unwrap();
+        assert_eq!([&r[..], &s[..], &[v]].concat(), data_item.get_signarure());
+
+        let signer = test_signer();
+        data_item.sign(&signer).await.unwrap();
+        assert!(matches!(
+            data_item.signature_rsv(),
+            Err(crate::error::BundlrError::InvalidSignerType)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sign_verify_stream_hashes_once() {
+        let path = &temp_path("test_data_item_stream_message");
+        let signer = test_signer();
+        let bytes = make_item(&signer, b"hello", &[]).await.unwrap();
+        fs::write(path, &bytes).unwrap();
+
+        // The data stream can only be read once, so verifying after signing relies on the
+        // message computed while signing
+        let mut file = File::open(path).unwrap();
+        let mut data_item =
+            BundlrTx::from_file_position(&mut file, bytes.len() as u64, 0, 4096).unwrap();
+        data_item.sign(&signer).await.unwrap();
+        assert!(data_item.verify().await.is_ok());
+
+        let other_signer = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        let mut data_item = BundlrTx::from_bytes(bytes).unwrap();
+        data_item.verify().await.unwrap();
+        data_item.sign(&other_signer).await.unwrap();
+        assert!(data_item.verify().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_wrong_declared_size() {
+        let path = &temp_path("test_data_item_wrong_size");
+        let signer = test_signer();
+        let bytes = make_item(&signer, b"hello", &[]).await.unwrap();
+        fs::write(path, &bytes).unwrap();
+
+        let mut file = File::open(path).unwrap();
+        let mut data_item =
+            BundlrTx::from_file_position(&mut file, bytes.len() as u64 + 10, 0, 4096).unwrap();
+        assert!(matches!(
+            data_item.verify().await,
+            Err(crate::error::BundlrError::NoBytesLeft)
+        ));
+
+        let mut data_item =
+            BundlrTx::from_file_position(&mut file, bytes.len() as u64, 0, 4096).unwrap();
+        assert!(data_item.verify().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_into_data() {
+        let path = &temp_path("test_data_item_into_data");
+        let signer = test_signer();
+        let bytes = make_item(&signer, &[7u8; 1000], &[]).await.unwrap();
+        fs::write(path, &bytes).unwrap();
+
+        let data_item = BundlrTx::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(data_item.into_data().unwrap(), vec![7u8; 1000]);
+
+        let mut file = File::open(path).unwrap();
+        let data_item = BundlrTx::from_file_position_with_chunk_size(
+            &mut file,
+            bytes.len() as u64,
+            0,
+            4096,
+            100,
+        )
+        .unwrap();
+        let (stream, len) = data_item.data_stream().unwrap();
+        let chunks = stream.try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(len, 1000);
+        assert_eq!(chunks.len(), 10);
+        assert_eq!(chunks.concat(), vec![7u8; 1000]);
+
+        let data_item =
+            BundlrTx::from_file_position(&mut file, bytes.len() as u64, 0, 4096).unwrap();
+        assert!(matches!(
+            data_item.into_data(),
+            Err(crate::error::BundlrError::InvalidDataType)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_many_tags_from_file_position() {
+        let path = &temp_path("test_data_item_many_tags");
+        let signer = test_signer();
+        let tags = (0..300)
+            .map(|i| Tag::new(&format!("tag-{}", i), "v"))
+            .collect::<Vec<_>>();
+        let bytes = make_item(&signer, b"hello", &tags).await.unwrap();
+        fs::write(path, &bytes).unwrap();
+
+        let mut file = File::open(path).unwrap();
+        let mut data_item =
+            BundlrTx::from_file_position(&mut file, bytes.len() as u64, 0, 8192).unwrap();
+        assert_eq!(data_item.get_tags(), &tags[..]);
+        assert!(data_item.verify().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_content_eq() {
+        let signer = test_signer();
+        let tags = vec![Tag::new("name", "value")];
+
+        let mut data_item_1 = BundlrTx::new(vec![], Vec::from("hello"), tags.clone()).unwrap();
+        data_item_1.sign(&signer).await.unwrap();
+        let data_item_2 = BundlrTx::new(vec![], Vec::from("hello"), tags.clone()).unwrap();
+        assert!(data_item_1.content_eq(&data_item_2));
+
+        let other_data = BundlrTx::new(vec![], Vec::from("world"), tags).unwrap();
+        assert!(!data_item_1.content_eq(&other_data));
+        let other_tags = BundlrTx::new(vec![], Vec::from("hello"), vec![]).unwrap();
+        assert!(!data_item_1.content_eq(&other_tags));
+    }
+
+    #[cfg(feature = "solana")]
+    #[tokio::test]
+    async fn test_header_bytes_and_byte_stream() -> Result<(), BundlrError> {
+        let path = &temp_path("test_byte_stream_item");
+        let signer = test_signer();
+        let data = vec![7u8; 10_000];
+        let mut tx = BundlrTx::new(vec![], data.clone(), vec![Tag::new("name", "value")])?;
+        tx.sign(&signer).await?;
+
+        let header = tx.header_bytes()?;
+        let bytes = tx.as_bytes()?;
+        assert_eq!([header.clone(), data].concat(), bytes);
+
+        let in_memory = BundlrTx::from_bytes(bytes.clone())?.as_byte_stream()?;
+        let in_memory: Vec<Bytes> = in_memory.try_collect().await.unwrap();
+        assert_eq!(in_memory.concat(), bytes);
+
+        fs::write(path, &bytes)?;
+        let mut file = File::open(path)?;
+        let streamed = BundlrTx::from_file_position(&mut file, bytes.len() as u64, 0, 4096)?;
+        assert_eq!(streamed.header_bytes()?, header);
+        let streamed: Vec<Bytes> = streamed.as_byte_stream()?.try_collect().await.unwrap();
+        assert_eq!(streamed.concat(), bytes);
+        Ok(())
+    }
+
+    #[cfg(feature = "solana")]
+    #[test]
+    fn test_is_bundle() {
+        let bundle_tags = |version: &str| {
+            vec![
+                Tag::new("Bundle-Format", "binary"),
+                Tag::new("Bundle-Version", version),
+            ]
+        };
+        let is_bundle = |tags| BundlrTx::new(vec![], vec![], tags).unwrap().is_bundle();
+
+        assert!(is_bundle(bundle_tags("2.0.0")));
+        assert!(!is_bundle(bundle_tags("1.0.0")));
+        assert!(!is_bundle(vec![Tag::new("Bundle-Format", "binary")]));
+        assert!(!is_bundle(vec![Tag::new("Content-Type", "text/plain")]));
+    }
+
+    #[cfg(feature = "solana")]
+    #[tokio::test]
+    async fn test_reject_truncated_signature_and_owner() -> Result<(), BundlrError> {
+        let signer = test_signer();
+        let bytes = make_item(&signer, b"hello", &[]).await?;
+
+        // Ed25519 items hold a 64 byte signature, then a 32 byte owner
+        assert!(matches!(
+            BundlrTx::from_bytes(bytes[..2 + 63].to_vec()),
+            Err(BundlrError::InvalidSignature)
+        ));
+        assert!(matches!(
+            BundlrTx::from_bytes(bytes[..2 + 64 + 31].to_vec()),
+            Err(BundlrError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            BundlrTx::verify_bytes(&bytes[..2 + 64 + 31]),
+            Err(BundlrError::InvalidKey(_))
+        ));
+        assert!(BundlrTx::from_bytes(bytes).is_ok());
+        Ok(())
+    }
+
+    #[cfg(feature = "solana")]
+    #[tokio::test]
+    async fn test_check_lengths() -> Result<(), BundlrError> {
+        use crate::index::SignerMap;
+
+        /// Ed25519 signer truncating its signature or public key by one byte
+        struct TruncatingSigner(Ed25519Signer, bool);
+
+        impl Signer for TruncatingSigner {
+            fn sign(&self, message: Bytes) -> Result<Bytes, BundlrError> {
+                let sig = self.0.sign(message)?;
+                Ok(if self.1 { sig.slice(1..) } else { sig })
+            }
+            fn sig_type(&self) -> SignerMap {
+                self.0.sig_type()
+            }
+            fn get_sig_length(&self) -> u16 {
+                self.0.get_sig_length()
+            }
+            fn get_pub_length(&self) -> u16 {
+                self.0.get_pub_length()
+            }
+            fn pub_key(&self) -> Bytes {
+                let pub_key = self.0.pub_key();
+                if self.1 {
+                    pub_key
+                } else {
+                    pub_key.slice(1..)
+                }
+            }
+        }
+
+        let mut tx = BundlrTx::new(vec![], b"hello".to_vec(), vec![])?;
+        tx.sign(&test_signer()).await?;
+        assert!(tx.check_lengths().is_ok());
+
+        let signer = TruncatingSigner(test_signer(), true);
+        tx.sign(&signer).await?;
+        assert!(matches!(
+            tx.verify().await,
+            Err(BundlrError::InvalidSignature)
+        ));
+        assert!(matches!(
+            tx.verify_with_owner(&signer.pub_key()).await,
+            Err(BundlrError::InvalidSignature)
+        ));
+
+        let signer = TruncatingSigner(test_signer(), false);
+        tx.sign(&signer).await?;
+        assert!(matches!(tx.verify().await, Err(BundlrError::InvalidKey(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_seeded_anchor() {
+        let tx = BundlrTx::new_with_rng(vec![], vec![], vec![], &mut SeededRng::new(42)).unwrap();
+        let other =
+            BundlrTx::new_with_rng(vec![], vec![], vec![], &mut SeededRng::new(42)).unwrap();
+        assert_eq!(tx.get_anchor(), other.get_anchor());
+        assert_eq!(tx.get_anchor().len(), 32);
+
+        let reseeded =
+            BundlrTx::new_with_rng(vec![], vec![], vec![], &mut SeededRng::new(43)).unwrap();
+        assert_ne!(tx.get_anchor(), reseeded.get_anchor());
+    }
+
+    #[test]
+    fn test_with_target_address() {
+        let mut tx = BundlrTx::new(vec![], vec![], vec![]).unwrap();
+        let target = [7u8; 32];
+        let address = BASE64URL_NOPAD.encode(&target);
+
+        tx.with_target_address(&address).unwrap();
+        assert_eq!(tx.target, target.to_vec());
+
+        let short = BASE64URL_NOPAD.encode(&[7u8; 31]);
+        assert!(matches!(
+            tx.with_target_address(&short),
+            Err(BundlrError::BytesError(_))
+        ));
+        assert!(matches!(
+            tx.with_target_address("not base64!"),
+            Err(BundlrError::Base64Error(_))
+        ));
+        assert_eq!(tx.target, target.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_with_anchor_tx() -> Result<(), BundlrError> {
+        let signer = test_signer();
+        let mut prev = BundlrTx::new(vec![], b"first".to_vec(), vec![])?;
+        prev.sign(&signer).await?;
+        let prev_hash = Sha256::digest(prev.get_signarure()).to_vec();
+        let prev_id = BASE64URL_NOPAD.encode(&prev_hash);
+
+        let mut tx = BundlrTx::new(vec![], b"second".to_vec(), vec![])?;
+        tx.with_anchor_tx(&prev_id)?;
+        tx.sign(&signer).await?;
+        let mut tx = BundlrTx::from_bytes(tx.as_bytes()?)?;
+        tx.verify().await?;
+        assert_eq!(tx.get_anchor(), prev_hash);
+
+        assert!(matches!(
+            tx.with_anchor_tx(""),
+            Err(BundlrError::BytesError(_))
+        ));
+        assert!(matches!(
+            tx.with_anchor_tx("not base64!"),
+            Err(BundlrError::Base64Error(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_receipt_request() -> Result<(), BundlrError> {
+        let signer = test_signer();
+        let tags = vec![
+            Tag::new("receipt-request", "TRUE"),
+            Tag::new("Deadline-Height", "1000"),
+        ];
+        let mut tx = BundlrTx::new(vec![], b"hello".to_vec(), tags)?;
+        tx.sign(&signer).await?;
+        let tx = BundlrTx::from_bytes(tx.as_bytes()?)?;
+        assert_eq!(
+            tx.receipt_request(),
+            Some(ReceiptRequest {
+                deadline_height: Some(1000)
+            })
+        );
+
+        let tx = BundlrTx::new(vec![], vec![], vec![Tag::new("Receipt-Request", "true")])?;
+        assert_eq!(tx.receipt_request(), Some(ReceiptRequest::default()));
+        let tx = BundlrTx::new(vec![], vec![], vec![Tag::new("Receipt-Request", "false")])?;
+        assert_eq!(tx.receipt_request(), None);
+        let tx = BundlrTx::new(vec![], vec![], vec![Tag::new("Deadline-Height", "1000")])?;
+        assert_eq!(tx.receipt_request(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialized_length_overflow() {
+        assert_eq!(serialized_length(&[2, 64, 32, 34, 16]).unwrap(), 148);
+        assert!(matches!(
+            serialized_length(&[2, 64, u64::MAX - 10]),
+            Err(BundlrError::ItemTooLarge)
+        ));
+        if usize::BITS < u64::BITS {
+            assert!(matches!(
+                serialized_length(&[usize::MAX as u64, 1]),
+                Err(BundlrError::ItemTooLarge)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_peek_signer_type() {
+        let ed25519 = test_signer();
+        let secp256k1 = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        let signers: [&dyn Signer; 2] = [&ed25519, &secp256k1];
+
+        for signer in signers {
+            let mut tx = BundlrTx::new(vec![], Vec::from("hello"), vec![]).unwrap();
+            tx.sign(signer).await.unwrap();
+            let bytes = tx.as_bytes().unwrap();
+            assert_eq!(
+                BundlrTx::peek_signer_type(&bytes).unwrap(),
+                signer.sig_type()
+            );
+        }
+
+        assert!(matches!(
+            BundlrTx::peek_signer_type(&[2]),
+            Err(BundlrError::NoBytesLeft)
+        ));
+        assert!(matches!(
+            BundlrTx::peek_signer_type(&[0, 0, 1]),
+            Err(BundlrError::InvalidSignerType)
+        ));
+    }
+
+    #[test]
+    fn test_verify_not_expired() {
+        let tx = BundlrTx::new(vec![], vec![], vec![]).unwrap();
+        assert!(tx.verify_not_expired(u64::MAX).is_ok());
+
+        let tx = BundlrTx::new(vec![], vec![], vec![Tag::new("Deadline-Height", "100")]).unwrap();
+        assert!(tx.verify_not_expired(99).is_ok());
+        assert!(tx.verify_not_expired(100).is_ok());
+        assert!(matches!(
+            tx.verify_not_expired(101),
+            Err(BundlrError::Expired(100))
+        ));
+
+        let tx = BundlrTx::new(vec![], vec![], vec![Tag::new("Deadline-Height", "soon")]).unwrap();
+        assert!(matches!(
+            tx.verify_not_expired(1),
+            Err(BundlrError::ParseError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reject_oversized_tags() {
+        let signer = test_signer();
+        let mut tx =
+            BundlrTx::new(vec![], Vec::from("hello"), vec![Tag::new("name", "value")]).unwrap();
+        tx.sign(&signer).await.unwrap();
+        let bytes = tx.as_bytes().unwrap();
+        // Signature type, signature, owner, target and anchor presence bytes and anchor
+        let tags_start = 2 + 64 + 32 + 1 + 1 + 32;
+
+        let mut huge_tags_bytes = bytes.clone();
+        huge_tags_bytes[tags_start + 8..tags_start + 16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            BundlrTx::from_bytes(huge_tags_bytes),
+            Err(BundlrError::InvalidTagEncoding)
+        ));
+
+        let mut past_buffer = bytes.clone();
+        let tags_bytes = (bytes.len() - tags_start - 16 + 1) as u64;
+        past_buffer[tags_start + 8..tags_start + 16].copy_from_slice(&tags_bytes.to_le_bytes());
+        assert!(matches!(
+            BundlrTx::from_bytes(past_buffer),
+            Err(BundlrError::InvalidTagEncoding)
+        ));
+
+        let limits = TagLimits {
+            max_tags: 1,
+            max_tags_bytes: 4,
+        };
+        assert!(matches!(
+            BundlrTx::from_bytes_with_limits(bytes.clone(), &limits),
+            Err(BundlrError::InvalidTagEncoding)
+        ));
+        assert!(BundlrTx::from_bytes_with_limits(bytes, &TagLimits::default()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_owner() {
+        let signer = test_signer();
+        let mut tx = BundlrTx::new(vec![], Vec::from("hello"), vec![]).unwrap();
+        tx.sign(&signer).await.unwrap();
+
+        assert!(tx.verify_with_owner(&signer.pub_key()).await.is_ok());
+        assert!(tx.verify_with_owner(&[0u8; 32]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_data() {
+        let signer = test_signer();
+        let mut tx =
+            BundlrTx::new(vec![], Vec::from("hello"), vec![Tag::new("name", "value")]).unwrap();
+        tx.sign(&signer).await.unwrap();
+        let bytes = tx.as_bytes().unwrap();
+        let header = &bytes[..bytes.len() - "hello".len()];
+
+        let mut tx = BundlrTx::from_header_bytes(header).unwrap();
+        assert_eq!(tx.get_tags(), &[Tag::new("name", "value")]);
+        assert!(tx.verify_with_data(b"hellO").await.is_err());
+        assert!(tx.verify_with_data(b"hello").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_app_tags() {
+        let signer = test_signer();
+        let mut tx =
+            BundlrTx::new(vec![], Vec::from("hello"), vec![Tag::new("name", "value")]).unwrap();
+        assert_eq!(tx.app(), None);
+
+        tx.set_app("my-app", "0.1.0");
+        tx.set_app("my-app", "0.2.0");
+        tx.sign(&signer).await.unwrap();
+
+        let tx = BundlrTx::from_bytes(tx.as_bytes().unwrap()).unwrap();
+        assert_eq!(tx.get_tags().len(), 3);
+        assert_eq!(tx.app(), Some(("my-app".to_string(), "0.2.0".to_string())));
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let mut tx = BundlrTx::new(
+            vec![],
+            vec![],
+            vec![
+                Tag::new("Content-Type", "image/jpeg"),
+                Tag::new("name", "a"),
+                Tag::new("content-type", "image/png"),
+                Tag::new("name", "b"),
+            ],
+        )
+        .unwrap();
+        tx.normalize_tags(crate::tags::SINGLETON_TAGS);
+
+        assert_eq!(
+            tx.tags,
+            vec![
+                Tag::new("name", "a"),
+                Tag::new("content-type", "image/png"),
+                Tag::new("name", "b"),
+            ]
+        );
+    }
 }
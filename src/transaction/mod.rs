@@ -1,5 +1,6 @@
 pub mod bundlr;
 pub mod poll;
+pub mod rng;
 
 #[derive(Debug)]
 pub struct TxStatus {
@@ -1,9 +1,6 @@
 use std::{thread::sleep, time::Duration};
 
-use crate::{
-    consts::{CONFIRMATIONS_NEEDED, RETRY_SLEEP},
-    currency::Currency,
-};
+use crate::{consts::RETRY_SLEEP, currency::Currency};
 
 pub struct ConfirmationPoll();
 
@@ -11,7 +8,7 @@ pub struct ConfirmationPoll();
 impl ConfirmationPoll {
     pub async fn await_confirmation(tx_id: &String, currency: &dyn Currency) {
         let mut confirmations = 0;
-        while confirmations < CONFIRMATIONS_NEEDED {
+        while confirmations < currency.min_confirmations() {
             let (status, tx_status) = match currency.get_tx_status(tx_id.to_string()).await {
                 Ok(ok) => ok,
                 Err(err) => continue,
@@ -0,0 +1,48 @@
+use rand::{
+    rngs::{OsRng, StdRng},
+    RngCore, SeedableRng,
+};
+
+use crate::error::BundlrError;
+
+/// Source of the random bytes used to generate transaction anchors
+pub trait RngSource {
+    fn fill(&mut self, dest: &mut [u8]) -> Result<(), BundlrError>;
+}
+
+/// Operating system randomness through `getrandom`, which unlike `ring` also supports wasm
+/// targets. Used by default.
+#[derive(Default)]
+pub struct SystemRng;
+
+impl SystemRng {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl RngSource for SystemRng {
+    fn fill(&mut self, dest: &mut [u8]) -> Result<(), BundlrError> {
+        OsRng
+            .try_fill_bytes(dest)
+            .map_err(|err| BundlrError::Unknown(err.to_string()))
+    }
+}
+
+/// Deterministic randomness from a seed, meant for tests and reproducible items only, as its
+/// output is predictable
+pub struct SeededRng(StdRng);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl RngSource for SeededRng {
+    fn fill(&mut self, dest: &mut [u8]) -> Result<(), BundlrError> {
+        self.0
+            .try_fill_bytes(dest)
+            .map_err(|err| BundlrError::Unknown(err.to_string()))
+    }
+}
@@ -0,0 +1,358 @@
+use bytes::Bytes;
+use futures::{
+    future,
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+
+use crate::error::BundlrError;
+
+/// Status and body of a node response
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub body: Bytes,
+}
+
+/// Status and body of a node response, with the body yielded in chunks as it is received
+pub struct HttpStreamResponse {
+    pub status: StatusCode,
+    pub body: BoxStream<'static, Result<Bytes, BundlrError>>,
+}
+
+/// HTTP transport used to talk to nodes. Implemented for `reqwest::Client`, other
+/// implementations allow running on custom runtimes or serving canned responses in tests.
+/// Query parameters are part of the url given to [`HttpTransport::get`].
+#[async_trait::async_trait]
+pub trait HttpTransport: Send + Sync {
+    async fn get(&self, url: Url, headers: &[(&str, &str)]) -> Result<HttpResponse, BundlrError>;
+
+    async fn post(
+        &self,
+        url: Url,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<HttpResponse, BundlrError>;
+
+    /// Same as [`HttpTransport::get`], but doesn't wait for the whole body, e.g. to write large
+    /// downloads to disk as they are received. Defaults to a single chunk with the whole body.
+    async fn get_stream(
+        &self,
+        url: Url,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpStreamResponse, BundlrError> {
+        let res = self.get(url, headers).await?;
+        Ok(HttpStreamResponse {
+            status: res.status,
+            body: stream::once(future::ready(Ok(res.body))).boxed(),
+        })
+    }
+
+    /// Same as [`HttpTransport::get`] without the body, e.g. to check whether a resource
+    /// exists. Defaults to a GET whose body is dropped.
+    async fn head(&self, url: Url, headers: &[(&str, &str)]) -> Result<HttpResponse, BundlrError> {
+        let res = self.get(url, headers).await?;
+        Ok(HttpResponse {
+            status: res.status,
+            body: Bytes::new(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpTransport for reqwest::Client {
+    async fn get(&self, url: Url, headers: &[(&str, &str)]) -> Result<HttpResponse, BundlrError> {
+        let mut req = reqwest::Client::get(self, url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+        into_response(res).await
+    }
+
+    async fn post(
+        &self,
+        url: Url,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<HttpResponse, BundlrError> {
+        let mut req = reqwest::Client::post(self, url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let res = req
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+        into_response(res).await
+    }
+
+    async fn get_stream(
+        &self,
+        url: Url,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpStreamResponse, BundlrError> {
+        let mut req = reqwest::Client::get(self, url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+        let status = res.status();
+        let body = stream::unfold(Some(res), |res| async move {
+            let mut res = res?;
+            match res.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(res))),
+                Ok(None) => None,
+                Err(err) => Some((Err(BundlrError::RequestError(err.to_string())), None)),
+            }
+        });
+        Ok(HttpStreamResponse {
+            status,
+            body: body.boxed(),
+        })
+    }
+
+    async fn head(&self, url: Url, headers: &[(&str, &str)]) -> Result<HttpResponse, BundlrError> {
+        let mut req = reqwest::Client::head(self, url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+        into_response(res).await
+    }
+}
+
+async fn into_response(res: reqwest::Response) -> Result<HttpResponse, BundlrError> {
+    let status = res.status();
+    let body = res
+        .bytes()
+        .await
+        .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+    Ok(HttpResponse { status, body })
+}
+
+/// Fails with `BundlrError::ResponseError` if the response status isn't a success, for
+/// responses whose body is ignored
+pub fn check_status(res: HttpResponse) -> Result<HttpResponse, BundlrError> {
+    if !res.status.is_success() {
+        let text = String::from_utf8_lossy(&res.body).replace('\"', "");
+        let msg = format!("Status: {}:{:?}", res.status, text);
+        return Err(BundlrError::ResponseError(msg));
+    }
+    Ok(res)
+}
+
+/// Same as [`check_status`], then deserializes the JSON body, failing with
+/// `BundlrError::ParseError` if it isn't a `T`
+pub fn check_response<T: for<'de> Deserialize<'de>>(res: HttpResponse) -> Result<T, BundlrError> {
+    let res = check_status(res)?;
+    serde_json::from_slice::<T>(&res.body).map_err(|err| BundlrError::ParseError(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+
+    use bytes::Bytes;
+    use num::BigUint;
+    use reqwest::{StatusCode, Url};
+
+    use super::{HttpResponse, HttpTransport};
+    use crate::{
+        bundlr::{get_balance, PubInfo},
+        currency::{solana::SolanaBuilder, CurrencyType},
+        error::BundlrError,
+        test_support::{temp_path, TEST_KEYPAIR},
+        BundlrBuilder,
+    };
+
+    /// Serves canned bodies keyed by method, path and query, recording requested urls
+    #[derive(Default)]
+    struct InMemoryTransport {
+        responses: HashMap<String, (StatusCode, &'static str)>,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl InMemoryTransport {
+        fn respond(&self, method: &str, url: &Url) -> Option<HttpResponse> {
+            let key = format!(
+                "{} {}?{}",
+                method,
+                url.path(),
+                url.query().unwrap_or_default()
+            );
+            self.requests.lock().unwrap().push(key.clone());
+            self.responses.get(&key).map(|(status, body)| HttpResponse {
+                status: *status,
+                body: Bytes::from_static(body.as_bytes()),
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpTransport for InMemoryTransport {
+        async fn get(
+            &self,
+            url: Url,
+            _headers: &[(&str, &str)],
+        ) -> Result<HttpResponse, BundlrError> {
+            Ok(self.respond("GET", &url).unwrap_or(HttpResponse {
+                status: StatusCode::NOT_FOUND,
+                body: Bytes::from_static(b"Not found"),
+            }))
+        }
+
+        async fn post(
+            &self,
+            url: Url,
+            _headers: &[(&str, &str)],
+            _body: Vec<u8>,
+        ) -> Result<HttpResponse, BundlrError> {
+            self.respond("POST", &url)
+                .ok_or_else(|| BundlrError::RequestError(format!("No response for {}", url)))
+        }
+    }
+
+    #[tokio::test]
+    async fn should_get_balance_through_transport() {
+        let mut transport = InMemoryTransport::default();
+        transport.responses.insert(
+            "GET /account/balance/solana?address=address".to_owned(),
+            (StatusCode::OK, "{ \"balance\": \"10\" }"),
+        );
+        let url = Url::parse("https://node.example").unwrap();
+
+        let balance = get_balance(&url, CurrencyType::Solana, "address", &transport)
+            .await
+            .unwrap();
+        assert_eq!(balance, BigUint::from(10u8));
+
+        let missing = get_balance(&url, CurrencyType::Solana, "other", &transport).await;
+        assert!(matches!(
+            missing.unwrap_err().root(),
+            BundlrError::TypeParseError(_)
+        ));
+        assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_upload_through_transport() {
+        let mut transport = InMemoryTransport::default();
+        transport.responses.insert(
+            "POST /tx/solana?".to_owned(),
+            (StatusCode::OK, "{ \"id\": \"id\" }"),
+        );
+        transport.responses.insert(
+            "GET /chunks/solana/-1/-1?".to_owned(),
+            (
+                StatusCode::OK,
+                "{ \"id\": \"upload\", \"min\": 1, \"max\": 1048576 }",
+            ),
+        );
+        transport.responses.insert(
+            "POST /chunks/solana/upload/0?".to_owned(),
+            (StatusCode::OK, ""),
+        );
+        let transport = Arc::new(transport);
+
+        let mut bundlr = BundlrBuilder::new()
+            .url(Url::parse("https://node.example").unwrap())
+            .currency(SolanaBuilder::new().wallet(TEST_KEYPAIR).build().unwrap())
+            .pub_info(PubInfo::default())
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let mut tx = bundlr
+            .create_transaction(b"hello".to_vec(), vec![])
+            .unwrap();
+        bundlr.sign_transaction(&mut tx).await.unwrap();
+        assert_eq!(bundlr.send_transaction(tx).await.unwrap().id, "id");
+
+        let path = &temp_path("test_transport_upload");
+        std::fs::write(path, b"hello").unwrap();
+        let res = bundlr.upload_file(PathBuf::from(path)).await;
+        assert!(res.is_ok());
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[0], "POST /tx/solana?");
+        assert_eq!(requests[1], "GET /chunks/solana/-1/-1?");
+        assert_eq!(requests[2], "POST /chunks/solana/upload/0?");
+    }
+
+    #[tokio::test]
+    async fn should_check_node_and_gateway_through_transport() {
+        let mut transport = InMemoryTransport::default();
+        transport.responses.insert(
+            "GET /info?".to_owned(),
+            (
+                StatusCode::OK,
+                "{ \"version\": \"0.2.0\", \"gateway\": \"gateway.example\", \"addresses\": {} }",
+            ),
+        );
+        transport
+            .responses
+            .insert("GET /tx/stored?".to_owned(), (StatusCode::OK, "{}"));
+        transport
+            .responses
+            .insert("GET /stored?".to_owned(), (StatusCode::OK, "hello"));
+        let transport = Arc::new(transport);
+
+        let pub_info = serde_json::from_str::<PubInfo>(
+            "{ \"version\": \"0.2.0\", \"gateway\": \"gateway.example\", \"addresses\": {} }",
+        )
+        .unwrap();
+        let bundlr = BundlrBuilder::new()
+            .url(Url::parse("https://node.example").unwrap())
+            .currency(SolanaBuilder::new().wallet(TEST_KEYPAIR).build().unwrap())
+            .pub_info(pub_info)
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        // Without a health endpoint, the health is read from the public info
+        let health = bundlr.health().await.unwrap();
+        assert_eq!(health.version.as_deref(), Some("0.2.0"));
+
+        let exist = bundlr
+            .which_exist(&["stored".to_owned(), "missing".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(exist, vec![true, false]);
+
+        let out = std::env::temp_dir().join("bundlr_transport_download");
+        bundlr
+            .download_streaming("stored", out.clone())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), b"hello");
+        std::fs::remove_file(&out).unwrap();
+
+        assert_eq!(
+            *transport.requests.lock().unwrap(),
+            vec![
+                "GET /health?",
+                "GET /info?",
+                "GET /stored?",
+                "GET /missing?",
+                "GET /tx/stored?",
+                "GET /stored?",
+            ]
+        );
+    }
+}
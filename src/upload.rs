@@ -1,12 +1,20 @@
-use std::{str::FromStr, thread::sleep, time::Duration};
+use std::{cmp, str::FromStr, sync::Arc, time::Duration};
 
-use reqwest::{header::ACCEPT, Url};
+use futures::{stream, StreamExt};
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+pub use tokio_util::sync::CancellationToken;
 
 use crate::{
-    consts::{BUNDLR_DEFAULT_URL, CHUNKS_RETRIES, CHUNKS_RETRY_SLEEP, CHUNK_SIZE},
+    consts::{
+        CHUNKING_VERSION, CHUNKS_RETRIES, CHUNKS_RETRY_SLEEP, CHUNK_SIZE, DEFAULT_BUNDLER_URL,
+    },
     currency::CurrencyType,
     error::BundlrError,
+    metrics::Metrics,
+    transport::{check_status, HttpTransport},
+    utils::with_trailing_slash,
 };
 
 #[derive(Serialize, Deserialize)]
@@ -14,26 +22,40 @@ struct IdRes {
     id: String,
     max: u64,
     min: u64,
+    #[serde(default, rename = "maxConcurrency")]
+    max_concurrency: Option<usize>,
 }
 
 pub struct Uploader {
     url: Url,
-    client: reqwest::Client,
+    transport: Arc<dyn HttpTransport>,
     pub upload_id: Option<String>,
     currency: CurrencyType,
     chunk_size: u64,
+    chunk_range: Option<(u64, u64)>,
+    max_concurrency: Option<usize>,
+    chunking_version: u16,
+    deadline: Option<Duration>,
+    metrics: Option<Arc<dyn Metrics>>,
+    extra_headers: Vec<(String, String)>,
 }
 
 impl Default for Uploader {
     fn default() -> Self {
-        let url = Url::from_str(BUNDLR_DEFAULT_URL).unwrap(); //Unwrap ok, never fails
+        let url = Url::from_str(DEFAULT_BUNDLER_URL).unwrap(); //Unwrap ok, never fails
         let client = reqwest::Client::new();
         Self {
             url,
-            client,
+            transport: Arc::new(client),
             upload_id: None,
             currency: CurrencyType::Arweave,
             chunk_size: CHUNK_SIZE,
+            chunk_range: None,
+            max_concurrency: None,
+            chunking_version: CHUNKING_VERSION,
+            deadline: None,
+            metrics: None,
+            extra_headers: vec![],
         }
     }
 }
@@ -41,57 +63,309 @@ impl Default for Uploader {
 impl Uploader {
     pub fn new(url: Url, client: reqwest::Client, currency: CurrencyType) -> Self {
         Uploader {
-            url,
-            client,
+            url: with_trailing_slash(url),
+            transport: Arc::new(client),
             upload_id: None,
             currency,
             chunk_size: CHUNK_SIZE,
+            chunk_range: None,
+            max_concurrency: None,
+            chunking_version: CHUNKING_VERSION,
+            deadline: None,
+            metrics: None,
+            extra_headers: vec![],
         }
     }
 
-    pub async fn upload(&mut self, _data: Vec<u8>) -> Result<(), BundlrError> {
-        let (max, min) = if let Some(upload_id) = self.upload_id.clone() {
-            let url = self
-                .url
-                .join(&format!("/chunks/{}/{}/-1", self.currency, upload_id))
-                .map_err(|err| BundlrError::ParseError(err.to_string()))?;
-            let res = self
-                .client
-                .get(url)
-                .header("x-chunking-version", "2")
-                .send()
-                .await
-                .map_err(|err| BundlrError::UploadError(err.to_string()))?
-                .json::<IdRes>()
-                .await
-                .map_err(|err| BundlrError::ParseError(err.to_string()))?;
-
-            (res.max, res.min)
-        } else {
-            let url = self
-                .url
-                .join(&format!("/chunks/{}/-1/-1", self.currency))
-                .map_err(|err| BundlrError::ParseError(err.to_string()))?;
-            let res = self
-                .client
-                .get(url)
-                .header("x-chunking-version", "2")
-                .send()
-                .await
-                .map_err(|err| BundlrError::UploadError(err.to_string()))?
-                .json::<IdRes>()
-                .await
-                .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+    /// Bounds the total time of an upload. The deadline is checked between chunks, so a slow
+    /// node makes the upload fail instead of hanging indefinitely. Once it is exceeded, the node
+    /// is asked to finalize the upload so it stops waiting for the missing chunks.
+    pub fn with_upload_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Overrides the chunked upload protocol version sent to the node, which defaults to
+    /// [`CHUNKING_VERSION`]
+    pub fn with_chunking_version(mut self, version: u16) -> Self {
+        self.chunking_version = version;
+        self
+    }
+
+    /// Sends the requests of uploads through `transport` instead of the `reqwest` client given
+    /// to [`Uploader::new`]
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Reports every chunk post to `metrics`, see [`Metrics::record_chunk`]
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Headers sent with every request of an upload, e.g. the API key of a private node, before
+    /// the headers given to [`Uploader::post_chunk`]
+    pub fn with_extra_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.extra_headers = headers;
+        self
+    }
+
+    /// Number of chunks `data_len` bytes are split into when uploaded
+    pub fn chunk_count(&self, data_len: u64) -> u64 {
+        data_len.div_ceil(self.chunk_size)
+    }
+
+    /// Chunk size range accepted by the node, as `(min, max)`, once negotiated by an upload
+    pub fn chunk_range(&self) -> Option<(u64, u64)> {
+        self.chunk_range
+    }
 
+    /// Maximum number of chunks the node accepts at the same time for an upload, if it
+    /// advertised one when the upload was negotiated
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.max_concurrency
+    }
+
+    /// Uploads `data` in chunks, starting a chunked upload or resuming the current one. The
+    /// chunk size is adjusted to the range the node accepts, see [`Uploader::chunk_range`].
+    pub async fn upload(&mut self, data: Vec<u8>) -> Result<(), BundlrError> {
+        self.upload_with_cancel(data, CancellationToken::new())
+            .await
+    }
+
+    /// Same as [`Uploader::upload`], but stops posting chunks once `cancel` is cancelled and
+    /// fails with `BundlrError::UploadError("cancelled")`. The chunk being posted is finished
+    /// first, then the node is asked to abort the upload and the upload id is cleared.
+    pub async fn upload_with_cancel(
+        &mut self,
+        data: Vec<u8>,
+        cancel: CancellationToken,
+    ) -> Result<(), BundlrError> {
+        let (min, max) = self.negotiate().await?;
+        self.fit_chunk_size(min, max);
+
+        let res = self.post_chunks(&data, &cancel).await;
+        self.abort_if_cancelled(res, &cancel).await
+    }
+
+    /// Starts a chunked upload, or resumes the current one, and stores the upload id and the
+    /// chunk size range returned by the node
+    async fn negotiate(&mut self) -> Result<(u64, u64), BundlrError> {
+        let upload_id = self.upload_id.as_deref().unwrap_or("-1");
+        let url = self
+            .url
+            .join(&format!("chunks/{}/{}/-1", self.currency, upload_id))
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+        let chunking_version = self.chunking_version.to_string();
+        let mut headers = vec![("x-chunking-version", chunking_version.as_str())];
+        for (header, value) in &self.extra_headers {
+            headers.push((header, value));
+        }
+        let res = self
+            .transport
+            .get(url, &headers)
+            .await
+            .map_err(|err| BundlrError::UploadError(err.to_string()))?;
+        let res = serde_json::from_slice::<IdRes>(&res.body)
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+
+        if res.min == 0 || res.max == 0 || res.min > res.max {
+            return Err(BundlrError::UploadError(format!(
+                "invalid chunk size range {} - {}",
+                res.min, res.max
+            )));
+        }
+
+        if self.upload_id.is_none() {
             self.upload_id = Some(res.id);
+        }
+        self.chunk_range = Some((res.min, res.max));
+        self.max_concurrency = res.max_concurrency;
+        Ok((res.min, res.max))
+    }
 
-            (res.max, res.min)
-        };
+    /// Moves the chunk size into the `min..=max` range accepted by the node, e.g. when an upload
+    /// negotiated with a node is resumed with another one. Chunks are posted at byte offsets and
+    /// a resumed upload posts every chunk again from offset 0, so all of them use the new size.
+    fn fit_chunk_size(&mut self, min: u64, max: u64) {
+        let chunk_size = self.chunk_size.clamp(min, max);
+        if chunk_size != self.chunk_size {
+            log::warn!(
+                "Chunk size {} is out of the node range {} - {}, using {}",
+                self.chunk_size,
+                min,
+                max,
+                chunk_size
+            );
+            self.chunk_size = chunk_size;
+        }
+    }
+
+    /// Same as [`Uploader::upload`], but posts up to `concurrency` chunks at the same time, or
+    /// fewer if the node advertises a lower limit. Chunks rejected with a 429 status are posted
+    /// again after a pause, with half the concurrency.
+    pub async fn upload_concurrent(
+        &mut self,
+        data: Vec<u8>,
+        concurrency: usize,
+    ) -> Result<(), BundlrError> {
+        self.upload_concurrent_with_cancel(data, concurrency, CancellationToken::new())
+            .await
+    }
+
+    /// Same as [`Uploader::upload_concurrent`], but stops posting chunks once `cancel` is
+    /// cancelled, see [`Uploader::upload_with_cancel`]
+    pub async fn upload_concurrent_with_cancel(
+        &mut self,
+        data: Vec<u8>,
+        concurrency: usize,
+        cancel: CancellationToken,
+    ) -> Result<(), BundlrError> {
+        let (min, max) = self.negotiate().await?;
+        self.fit_chunk_size(min, max);
+
+        let res = self
+            .post_chunks_concurrent(&data, concurrency, &cancel)
+            .await;
+        self.abort_if_cancelled(res, &cancel).await
+    }
+
+    /// Posts `data` with up to `concurrency` chunks at the same time, see
+    /// [`Uploader::upload_concurrent`]
+    async fn post_chunks_concurrent(
+        &self,
+        data: &[u8],
+        concurrency: usize,
+        cancel: &CancellationToken,
+    ) -> Result<(), BundlrError> {
+        let start = Instant::now();
+        let chunk_size = self.chunk_size as usize;
+        let mut concurrency = self.concurrency_limit(concurrency);
+        let mut pending = (0..data.len()).step_by(chunk_size).collect::<Vec<_>>();
+        while !pending.is_empty() {
+            self.check_deadline(start).await?;
+
+            let results = stream::iter(pending)
+                .map(|offset| async move {
+                    if cancel.is_cancelled() {
+                        return Err(BundlrError::UploadError("cancelled".to_string()));
+                    }
+                    let chunk = &data[offset..cmp::min(offset + chunk_size, data.len())];
+                    match self.post_chunk(chunk, offset, vec![]).await {
+                        Ok(_) => Ok(None),
+                        Err(BundlrError::TooManyRequests) => Ok(Some(offset)),
+                        Err(_) => self
+                            .post_chunk_with_retries(chunk.to_vec(), offset, vec![])
+                            .await
+                            .map(|_| None),
+                    }
+                })
+                .buffer_unordered(concurrency)
+                .collect::<Vec<_>>()
+                .await;
+
+            // Chunks the node was too busy to accept
+            pending = results
+                .into_iter()
+                .filter_map(Result::transpose)
+                .collect::<Result<Vec<_>, _>>()?;
+            if !pending.is_empty() {
+                concurrency = cmp::max(concurrency / 2, 1);
+                tokio::time::sleep(Duration::from_secs(CHUNKS_RETRY_SLEEP)).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails with `BundlrError::UploadError("deadline exceeded")` if the upload started at
+    /// `start` exceeded its deadline, after asking the node to finalize it
+    async fn check_deadline(&self, start: Instant) -> Result<(), BundlrError> {
+        match self.deadline {
+            Some(deadline) if start.elapsed() > deadline => {
+                if let Err(err) = self.finalize().await {
+                    log::warn!("Could not finalize upload after its deadline: {}", err);
+                }
+                Err(BundlrError::UploadError("deadline exceeded".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
 
-        if self.chunk_size < min || self.chunk_size > max {
-            return Err(BundlrError::ChunkSizeOutOfRange(min, max));
+    /// Asks the node to abort the current chunked upload if `res` failed because `cancel` was
+    /// cancelled, then clears the upload id as it can't be resumed anymore
+    async fn abort_if_cancelled(
+        &mut self,
+        res: Result<(), BundlrError>,
+        cancel: &CancellationToken,
+    ) -> Result<(), BundlrError> {
+        if res.is_err() && cancel.is_cancelled() {
+            if let Err(err) = self.post_upload_action("abort").await {
+                log::warn!("Could not abort cancelled upload: {}", err);
+            }
+            self.upload_id = None;
         }
+        res
+    }
 
+    /// Asks the node to finalize the current chunked upload with the chunks posted so far
+    async fn finalize(&self) -> Result<(), BundlrError> {
+        self.post_upload_action("-1").await
+    }
+
+    /// Posts `action` for the current chunked upload, e.g. `-1` to finalize it or `abort`
+    async fn post_upload_action(&self, action: &str) -> Result<(), BundlrError> {
+        let upload_id = match &self.upload_id {
+            Some(id) => id,
+            None => return Err(BundlrError::UploadError("No upload id".to_string())),
+        };
+        let url = self
+            .url
+            .join(&format!(
+                "chunks/{}/{}/{}",
+                self.currency, upload_id, action
+            ))
+            .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+        let chunking_version = self.chunking_version.to_string();
+        let mut headers = vec![("x-chunking-version", chunking_version.as_str())];
+        for (header, value) in &self.extra_headers {
+            headers.push((header, value));
+        }
+        self.transport
+            .post(url, &headers, vec![])
+            .await
+            .and_then(check_status)
+            .map(|_| ())
+    }
+
+    /// Concurrency to upload chunks with, given the `requested` one and the node limit
+    fn concurrency_limit(&self, requested: usize) -> usize {
+        let limit = match self.max_concurrency {
+            Some(advertised) => cmp::min(requested, advertised),
+            None => requested,
+        };
+        cmp::max(limit, 1)
+    }
+
+    /// Posts `data` chunk by chunk, aborting if the upload deadline is exceeded or `cancel` is
+    /// cancelled
+    async fn post_chunks(
+        &self,
+        data: &[u8],
+        cancel: &CancellationToken,
+    ) -> Result<(), BundlrError> {
+        let start = Instant::now();
+        let mut offset = 0;
+        for chunk in data.chunks(self.chunk_size as usize) {
+            if cancel.is_cancelled() {
+                return Err(BundlrError::UploadError("cancelled".to_string()));
+            }
+            self.check_deadline(start).await?;
+            self.post_chunk_with_retries(chunk.to_vec(), offset, vec![])
+                .await?;
+            offset += chunk.len();
+        }
         Ok(())
     }
 
@@ -124,7 +398,7 @@ impl Uploader {
                 Ok(offset) => return Ok(offset),
                 Err(e) => {
                     dbg!("post_chunk_with_retries: {:?}", e);
-                    sleep(Duration::from_secs(CHUNKS_RETRY_SLEEP));
+                    tokio::time::sleep(Duration::from_secs(CHUNKS_RETRY_SLEEP)).await;
                     retries += 1;
                     resp = self.post_chunk(&chunk, offset, headers.clone()).await;
                 }
@@ -138,6 +412,20 @@ impl Uploader {
         chunk: &[u8],
         offset: usize,
         headers: Vec<(String, String)>,
+    ) -> Result<usize, BundlrError> {
+        let start = Instant::now();
+        let res = self.send_chunk(chunk, offset, headers).await;
+        if let Some(metrics) = &self.metrics {
+            metrics.record_chunk(chunk.len() as u64, start.elapsed(), res.is_ok());
+        }
+        res
+    }
+
+    async fn send_chunk(
+        &self,
+        chunk: &[u8],
+        offset: usize,
+        headers: Vec<(String, String)>,
     ) -> Result<usize, BundlrError> {
         let upload_id = match &self.upload_id {
             Some(id) => id,
@@ -146,28 +434,332 @@ impl Uploader {
         let url = self
             .url
             .join(&format!(
-                "/chunks/{}/{}/{}",
+                "chunks/{}/{}/{}",
                 self.currency, upload_id, offset
             ))
             .map_err(|err| BundlrError::ParseError(err.to_string()))?;
 
-        let mut req = self
-            .client
-            .post(url)
-            .json(&chunk)
-            .header(&ACCEPT, "application/json");
-        for (header, value) in headers {
-            req = req.header(header, value);
+        let body =
+            serde_json::to_vec(&chunk).map_err(|err| BundlrError::ParseError(err.to_string()))?;
+        let mut all_headers = vec![
+            ("Content-Type", "application/json"),
+            ("Accept", "application/json"),
+        ];
+        for (header, value) in self.extra_headers.iter().chain(&headers) {
+            all_headers.push((header, value));
         }
 
-        let res = req
-            .send()
+        let res = self
+            .transport
+            .post(url, &all_headers, body)
             .await
             .map_err(|e| BundlrError::PostChunkError(e.to_string()))?;
 
-        match res.status() {
+        match res.status {
             reqwest::StatusCode::OK => Ok(offset),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(BundlrError::TooManyRequests),
             err => Err(BundlrError::RequestError(err.to_string())),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use regex::Regex;
+    use reqwest::{StatusCode, Url};
+
+    use super::{CancellationToken, Uploader};
+    use crate::{
+        consts::{CHUNK_SIZE, DEFAULT_BUNDLER_URL},
+        currency::CurrencyType,
+        error::BundlrError,
+        metrics::Metrics,
+        transport::{HttpResponse, HttpTransport},
+    };
+
+    #[test]
+    fn should_default_to_irys() {
+        assert_eq!(DEFAULT_BUNDLER_URL, "https://uploader.irys.xyz/");
+        assert_eq!(Uploader::default().url.as_str(), DEFAULT_BUNDLER_URL);
+    }
+
+    #[test]
+    fn should_count_chunks() {
+        let uploader = Uploader::default();
+        assert_eq!(uploader.chunk_count(0), 0);
+        assert_eq!(uploader.chunk_count(1), 1);
+        assert_eq!(uploader.chunk_count(CHUNK_SIZE), 1);
+        assert_eq!(uploader.chunk_count(CHUNK_SIZE + 1), 2);
+        assert_eq!(uploader.chunk_count(10 * CHUNK_SIZE), 10);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_chunk_range() {
+        let server = MockServer::start();
+        let mut mock = server.mock(|when, then| {
+            when.method(GET).path("/chunks/solana/-1/-1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"id\": \"upload_id\", \"min\": 1000, \"max\": 10 }");
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana);
+        match uploader.upload(vec![0u8; 50]).await {
+            Err(BundlrError::UploadError(msg)) => {
+                assert_eq!(msg, "invalid chunk size range 1000 - 10")
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert_eq!(uploader.chunk_range(), None);
+        assert_eq!(uploader.upload_id, None);
+        mock.delete();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/chunks/solana/-1/-1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"id\": \"upload_id\", \"min\": 10, \"max\": 1000 }");
+        });
+        assert_eq!(uploader.negotiate().await.unwrap(), (10, 1000));
+        assert_eq!(uploader.chunk_range(), Some((10, 1000)));
+        assert_eq!(uploader.upload_id.as_deref(), Some("upload_id"));
+    }
+
+    #[tokio::test]
+    async fn should_send_chunking_version() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/chunks/solana/-1/-1")
+                .header("x-chunking-version", "3");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"id\": \"upload_id\", \"min\": 10, \"max\": 1000 }");
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana)
+            .with_chunking_version(3);
+        assert!(uploader.negotiate().await.is_ok());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn should_honor_advertised_concurrency() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/chunks/solana/-1/-1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    "{ \"id\": \"upload_id\", \"min\": 10, \"max\": 1000, \"maxConcurrency\": 2 }",
+                );
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana);
+        assert_eq!(uploader.concurrency_limit(8), 8);
+        uploader.negotiate().await.unwrap();
+        assert_eq!(uploader.max_concurrency(), Some(2));
+        assert_eq!(uploader.concurrency_limit(8), 2);
+        assert_eq!(uploader.concurrency_limit(1), 1);
+        assert_eq!(uploader.concurrency_limit(0), 1);
+    }
+
+    static CHUNK_REJECTED: AtomicBool = AtomicBool::new(false);
+
+    #[tokio::test(start_paused = true)]
+    async fn should_repost_chunks_rejected_with_429() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/chunks/solana/-1/-1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"id\": \"upload_id\", \"min\": 10, \"max\": 1000 }");
+        });
+        let accepted = server.mock(|when, then| {
+            when.method(POST)
+                .path_matches(Regex::new("/chunks/solana/upload_id/(0|20)$").unwrap());
+            then.status(200);
+        });
+        // The node is busy for the first post of the chunk at offset 10 only
+        let busy = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chunks/solana/upload_id/10")
+                .matches(|_| !CHUNK_REJECTED.swap(true, Ordering::SeqCst));
+            then.status(429);
+        });
+        let reposted = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chunks/solana/upload_id/10")
+                .matches(|_| CHUNK_REJECTED.load(Ordering::SeqCst));
+            then.status(200);
+        });
+
+        let url = Url::parse(&server.url("/")).unwrap();
+        let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana);
+        uploader.chunk_size = 10;
+        uploader.upload_concurrent(vec![0u8; 30], 3).await.unwrap();
+
+        busy.assert_hits(1);
+        reposted.assert_hits(1);
+        accepted.assert_hits(2);
+    }
+
+    /// Node answering every request with a 200 after `delay`, recording the posted paths
+    struct SlowNode {
+        delay: Duration,
+        posted: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpTransport for SlowNode {
+        async fn get(
+            &self,
+            url: Url,
+            _headers: &[(&str, &str)],
+        ) -> Result<HttpResponse, BundlrError> {
+            Err(BundlrError::RequestError(format!("unexpected GET {}", url)))
+        }
+
+        async fn post(
+            &self,
+            url: Url,
+            _headers: &[(&str, &str)],
+            _body: Vec<u8>,
+        ) -> Result<HttpResponse, BundlrError> {
+            tokio::time::sleep(self.delay).await;
+            self.posted.lock().unwrap().push(url.path().to_string());
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                body: Bytes::new(),
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_abort_upload_after_deadline() {
+        let node = Arc::new(SlowNode {
+            delay: Duration::from_millis(200),
+            posted: Mutex::new(vec![]),
+        });
+        let url = Url::parse("http://node.test/").unwrap();
+        let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana)
+            .with_transport(node.clone())
+            .with_upload_deadline(Duration::from_millis(300));
+        uploader.upload_id = Some("upload_id".to_string());
+        uploader.chunk_size = 10;
+
+        let res = uploader
+            .post_chunks(&[0u8; 50], &CancellationToken::new())
+            .await;
+        match res {
+            Err(BundlrError::UploadError(msg)) => assert_eq!(msg, "deadline exceeded"),
+            res => panic!("unexpected result: {:?}", res),
+        }
+        // Two chunks fit in the deadline, then the upload is finalized
+        assert_eq!(
+            *node.posted.lock().unwrap(),
+            vec![
+                "/chunks/solana/upload_id/0",
+                "/chunks/solana/upload_id/10",
+                "/chunks/solana/upload_id/-1",
+            ]
+        );
+    }
+
+    /// Cancels the upload once its first chunk is posted
+    struct CancelAfterFirstChunk(CancellationToken);
+
+    impl Metrics for CancelAfterFirstChunk {
+        fn record_chunk(&self, _bytes: u64, _duration: Duration, _success: bool) {
+            self.0.cancel();
+        }
+    }
+
+    #[tokio::test]
+    async fn should_abort_upload_once_cancelled() {
+        let chunk_path = Regex::new("/chunks/solana/upload_id/[0-9]+$").unwrap();
+        for concurrent in [false, true] {
+            let server = MockServer::start();
+            server.mock(|when, then| {
+                when.method(GET).path("/chunks/solana/-1/-1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body("{ \"id\": \"upload_id\", \"min\": 10, \"max\": 10 }");
+            });
+            let chunks = server.mock(|when, then| {
+                when.method(POST).path_matches(chunk_path.clone());
+                then.status(200);
+            });
+            let aborted = server.mock(|when, then| {
+                when.method(POST).path("/chunks/solana/upload_id/abort");
+                then.status(200);
+            });
+
+            let url = Url::parse(&server.url("/")).unwrap();
+            let cancel = CancellationToken::new();
+            let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana)
+                .with_metrics(Arc::new(CancelAfterFirstChunk(cancel.clone())));
+            let res = if concurrent {
+                uploader
+                    .upload_concurrent_with_cancel(vec![0u8; 50], 1, cancel)
+                    .await
+            } else {
+                uploader.upload_with_cancel(vec![0u8; 50], cancel).await
+            };
+            match res {
+                Err(BundlrError::UploadError(msg)) => assert_eq!(msg, "cancelled"),
+                res => panic!("unexpected result: {:?}", res),
+            }
+            chunks.assert_hits(1);
+            aborted.assert_hits(1);
+            assert_eq!(uploader.upload_id, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fit_chunk_size_to_node_range() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/chunks/solana/upload_id/-1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("{ \"id\": \"upload_id\", \"min\": 10, \"max\": 20 }");
+        });
+        let chunks = server.mock(|when, then| {
+            when.method(POST)
+                .path_matches(Regex::new("/chunks/solana/upload_id/(0|20|40)$").unwrap());
+            then.status(200);
+        });
+
+        // Resumes an upload negotiated with a node accepting bigger chunks
+        let url = Url::parse(&server.url("/")).unwrap();
+        let mut uploader = Uploader::new(url, reqwest::Client::new(), CurrencyType::Solana);
+        uploader.upload_id = Some("upload_id".to_string());
+        uploader.chunk_size = 100;
+        uploader.upload(vec![0u8; 50]).await.unwrap();
+
+        assert_eq!(uploader.chunk_size, 20);
+        assert_eq!(uploader.chunk_count(50), 3);
+        chunks.assert_hits(3);
+
+        uploader.chunk_size = 1;
+        uploader.fit_chunk_size(10, 20);
+        assert_eq!(uploader.chunk_size, 10);
+    }
+}
@@ -14,7 +14,7 @@ lazy_static! {
     static ref IDENT_REGEX: Regex = Regex::new(r"^[a-zA-Z_$][a-zA-Z_$0-9 ]*$").unwrap();
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct EIP712Domain {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -74,6 +74,96 @@ impl Validate for EIP712 {
     }
 }
 
+/// Builds an [`EIP712`] struct from its domain fields and user supplied types and message,
+/// without writing its JSON. The `EIP712Domain` type is derived from the fields that are set,
+/// unless `types` declares it.
+#[derive(Debug, Clone, Default)]
+pub struct Eip712DomainBuilder {
+    domain: EIP712Domain,
+}
+
+impl Eip712DomainBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.domain.name = Some(name.to_owned());
+        self
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.domain.version = Some(version.to_owned());
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.domain.chain_id = Some(U256::from(chain_id));
+        self
+    }
+
+    pub fn verifying_contract(mut self, verifying_contract: [u8; 20]) -> Self {
+        self.domain.verifying_contract = Some(Address::from(verifying_contract));
+        self
+    }
+
+    pub fn salt(mut self, salt: [u8; 32]) -> Self {
+        self.domain.salt = Some(H256::from(salt));
+        self
+    }
+
+    /// `types` maps every struct name to its fields, as in the EIP-712 JSON format
+    pub fn build(
+        self,
+        types: Value,
+        primary_type: &str,
+        message: Value,
+    ) -> Result<EIP712, Eip712Error> {
+        validate_domain(&self.domain).map_err(Eip712Error::ValidationError)?;
+
+        let mut types = serde_json::from_value::<MessageTypes>(types)
+            .map_err(|_| serde_error("message types", Some("types")))?;
+        types
+            .entry("EIP712Domain".to_owned())
+            .or_insert_with(|| self.domain_types());
+
+        let typed_data = EIP712 {
+            types,
+            primary_type: primary_type.to_owned(),
+            message,
+            domain: self.domain,
+        };
+        typed_data
+            .validate()
+            .map_err(Eip712Error::ValidationErrors)?;
+        Ok(typed_data)
+    }
+
+    fn domain_types(&self) -> Vec<FieldType> {
+        let field = |name: &str, type_: &str| FieldType {
+            name: name.to_owned(),
+            type_: type_.to_owned(),
+        };
+        let mut fields = vec![];
+        if self.domain.name.is_some() {
+            fields.push(field("name", "string"));
+        }
+        if self.domain.version.is_some() {
+            fields.push(field("version", "string"));
+        }
+        if self.domain.chain_id.is_some() {
+            fields.push(field("chainId", "uint256"));
+        }
+        if self.domain.verifying_contract.is_some() {
+            fields.push(field("verifyingContract", "address"));
+        }
+        if self.domain.salt.is_some() {
+            fields.push(field("salt", "bytes32"));
+        }
+        fields
+    }
+}
+
 #[derive(Validate, Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct FieldType {
     #[validate(regex = "IDENT_REGEX")]
@@ -86,6 +176,7 @@ pub(crate) struct FieldType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rustc_hex::ToHex;
     use serde_json::from_str;
 
     #[test]
@@ -254,6 +345,49 @@ mod tests {
         let data = from_str::<EIP712>(string).unwrap();
         assert_eq!(data.validate().is_err(), true);
     }
+
+    #[test]
+    fn should_build_domain() {
+        let types = serde_json::json!({
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        });
+        let message = serde_json::json!({
+            "from": {
+                "name": "Cow",
+                "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+            },
+            "to": {
+                "name": "Bob",
+                "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+            },
+            "contents": "Hello, Bob!"
+        });
+
+        let typed_data = Eip712DomainBuilder::new()
+            .name("Ether Mail")
+            .version("1")
+            .chain_id(1)
+            .verifying_contract([0xcc; 20])
+            .build(types.clone(), "Mail", message.clone())
+            .unwrap();
+        assert_eq!(
+            hash_structured_data(typed_data).unwrap().to_hex::<String>(),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        assert!(matches!(
+            Eip712DomainBuilder::new().build(types, "Mail", message),
+            Err(Eip712Error::ValidationError(_))
+        ));
+    }
 }
 
 mod encode;
@@ -263,3 +397,5 @@ mod parser;
 
 pub use encode::hash_structured_data;
 pub use error::Eip712Error;
+
+use error::serde_error;
@@ -1,9 +1,10 @@
 #[cfg(any(feature = "ethereum", feature = "erc20"))]
 mod eip712;
 
-pub(crate) use eip712::hash_structured_data;
-pub(crate) use eip712::Eip712Error;
-pub(crate) use eip712::EIP712;
+pub use eip712::hash_structured_data;
+pub use eip712::Eip712DomainBuilder;
+pub use eip712::Eip712Error;
+pub use eip712::EIP712;
 
 use std::{
     fs::File,
@@ -14,7 +15,11 @@ use bytes::Bytes;
 use reqwest::{Response, Url};
 use serde::Deserialize;
 
-use crate::error::BundlrError;
+use crate::{
+    consts::LEGACY_BUNDLR_HOSTS,
+    error::BundlrError,
+    transport::{check_response, HttpTransport},
+};
 
 pub async fn check_and_return<T: for<'de> Deserialize<'de>>(
     res: Result<Response, reqwest::Error>,
@@ -41,22 +46,82 @@ where
 }
 
 pub async fn get_nonce(
-    client: &reqwest::Client,
+    client: &(impl HttpTransport + ?Sized),
     url: &Url,
     address: String,
     currency: String,
 ) -> Result<u64, BundlrError> {
-    let res = client
+    client
         .get(
             url.join(&format!(
-                "/account/withdrawals/{}?address={}",
+                "account/withdrawals/{}?address={}",
                 currency, address
             ))
             .map_err(|err| BundlrError::ParseError(err.to_string()))?,
+            &[],
         )
-        .send()
-        .await;
-    check_and_return::<u64>(res).await
+        .await
+        .and_then(check_response::<u64>)
+}
+
+/// Maps the url of a deprecated Bundlr network node to its Irys equivalent, logging a warning.
+/// Other urls are returned unchanged.
+pub fn migrate_legacy_url(mut url: Url) -> Url {
+    let irys_host = url.host_str().and_then(|host| {
+        LEGACY_BUNDLR_HOSTS
+            .iter()
+            .find(|(legacy, _)| legacy.eq_ignore_ascii_case(host))
+            .map(|(_, irys)| *irys)
+    });
+    if let Some(irys_host) = irys_host {
+        log::warn!(
+            "{} belongs to the deprecated Bundlr network, using {} instead",
+            url,
+            irys_host
+        );
+        // Unwrap ok, the url already has a valid host
+        url.set_host(Some(irys_host)).unwrap();
+    }
+    url
+}
+
+/// Appends a trailing slash to the path of a node url, so that relative endpoints joined to it
+/// keep any path prefix, e.g. `http://127.0.0.1:8080/node` + `info` gives
+/// `http://127.0.0.1:8080/node/info` instead of `http://127.0.0.1:8080/info`
+pub(crate) fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url
+}
+
+/// Parses the gateway of a node public info, which may lack a scheme. Gateways on loopback
+/// hosts, like a local dev node's, default to plain http, others to https.
+pub(crate) fn gateway_base_url(gateway: &str) -> Result<Url, BundlrError> {
+    let gateway = if gateway.contains("://") {
+        gateway.to_owned()
+    } else {
+        let host = gateway.split(['/', ':']).next().unwrap_or_default();
+        let loopback = host.eq_ignore_ascii_case("localhost")
+            || host
+                .parse::<std::net::Ipv4Addr>()
+                .map(|ip| ip.is_loopback())
+                .unwrap_or(false)
+            || gateway.starts_with("[::1]");
+        let scheme = if loopback { "http" } else { "https" };
+        format!("{}://{}", scheme, gateway)
+    };
+    Url::parse(&gateway)
+        .map(with_trailing_slash)
+        .map_err(|err| BundlrError::ParseError(err.to_string()))
+}
+
+/// Encodes `value` as the 32 byte little endian integer used in bundle headers
+pub(crate) fn u256_le(value: u64) -> [u8; 32] {
+    let mut b = [0u8; 32];
+    b[..8].copy_from_slice(&value.to_le_bytes());
+    b
 }
 
 // Reads `length` bytes at `offset` within `file`
@@ -72,3 +137,48 @@ pub fn read_offset(file: &mut File, offset: u64, length: usize) -> Result<Bytes,
     file.read(&mut b)?;
     Ok(b.into())
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use super::{gateway_base_url, migrate_legacy_url, with_trailing_slash};
+
+    #[test]
+    fn should_migrate_legacy_urls() {
+        let url = migrate_legacy_url(Url::parse("https://node1.bundlr.network/info").unwrap());
+        assert_eq!(url.as_str(), "https://node1.irys.xyz/info");
+        let url = migrate_legacy_url(Url::parse("https://DEVNET.bundlr.network").unwrap());
+        assert_eq!(url.as_str(), "https://devnet.irys.xyz/");
+
+        let url = Url::parse("http://localhost:1984/").unwrap();
+        assert_eq!(migrate_legacy_url(url.clone()), url);
+    }
+
+    #[test]
+    fn should_keep_node_path_prefix() {
+        let url = with_trailing_slash(Url::parse("http://127.0.0.1:8080").unwrap());
+        assert_eq!(
+            url.join("info").unwrap().as_str(),
+            "http://127.0.0.1:8080/info"
+        );
+        let url = with_trailing_slash(Url::parse("http://127.0.0.1:8080/node").unwrap());
+        assert_eq!(
+            url.join("info").unwrap().as_str(),
+            "http://127.0.0.1:8080/node/info"
+        );
+    }
+
+    #[test]
+    fn should_default_local_gateways_to_http() {
+        for (gateway, expected) in [
+            ("arweave.net", "https://arweave.net/"),
+            ("localhost:1984", "http://localhost:1984/"),
+            ("127.0.0.1:1984", "http://127.0.0.1:1984/"),
+            ("[::1]:1984", "http://[::1]:1984/"),
+            ("https://localhost:1984", "https://localhost:1984/"),
+        ] {
+            assert_eq!(gateway_base_url(gateway).unwrap().as_str(), expected);
+        }
+    }
+}
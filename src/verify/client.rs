@@ -0,0 +1,94 @@
+use crate::{error::BundlrError, tags::TagLimits, BundlrTx};
+
+use super::{
+    file::{verify_file_bundle_with_options, VerifyOptions},
+    types::Item,
+};
+
+/// Entry point for verification-only deployments, parsing and verifying items and bundles
+/// without building a currency nor a node client
+#[derive(Debug, Clone, Default)]
+pub struct VerifierClient {
+    options: VerifyOptions,
+    tag_limits: TagLimits,
+}
+
+impl VerifierClient {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the options used to verify bundles, and the maximum size of single items
+    pub fn with_options(mut self, options: VerifyOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the bounds on the tags of parsed single items
+    pub fn with_tag_limits(mut self, tag_limits: TagLimits) -> Self {
+        self.tag_limits = tag_limits;
+        self
+    }
+
+    /// Parses and verifies the serialized item `bytes`, failing with
+    /// [`BundlrError::ItemTooLarge`] if it exceeds [`VerifyOptions::max_item_size`] and with
+    /// [`BundlrError::InvalidSignerType`] if its scheme isn't in
+    /// [`VerifyOptions::allowed_schemes`]
+    pub async fn verify_item(&self, bytes: Vec<u8>) -> Result<BundlrTx, BundlrError> {
+        if let Some(max_item_size) = self.options.max_item_size {
+            if bytes.len() as u64 > max_item_size {
+                return Err(BundlrError::ItemTooLarge);
+            }
+        }
+        self.options.check_scheme(&bytes)?;
+        let mut item = BundlrTx::from_bytes_with_limits(bytes, &self.tag_limits)?;
+        item.verify().await?;
+        Ok(item)
+    }
+
+    /// Verifies every item of the bundle file `filename`, see
+    /// [`verify_file_bundle_with_options`]
+    pub fn verify_bundle_file(&self, filename: &str) -> Result<Vec<Item>, BundlrError> {
+        verify_file_bundle_with_options(filename, &self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::VerifierClient;
+    use crate::{
+        error::BundlrError,
+        tags::Tag,
+        test_support::{make_bundle, make_item, temp_path, test_signer},
+        verify::file::VerifyOptions,
+    };
+
+    #[tokio::test]
+    async fn should_verify_without_client() -> Result<(), BundlrError> {
+        let path = &temp_path("test_verifier_client_bundle");
+        let bytes = make_item(&test_signer(), b"hello", &[Tag::new("name", "value")]).await?;
+
+        let verifier = VerifierClient::new();
+        let item = verifier.verify_item(bytes.clone()).await?;
+        assert_eq!(item.get_tags(), &[Tag::new("name", "value")]);
+
+        fs::write(path, make_bundle(std::slice::from_ref(&bytes))?)?;
+        assert_eq!(verifier.verify_bundle_file(path)?.len(), 1);
+
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(verifier.verify_item(tampered).await.is_err());
+
+        let strict = VerifierClient::new().with_options(VerifyOptions {
+            max_item_size: Some(bytes.len() as u64 - 1),
+            ..Default::default()
+        });
+        assert!(matches!(
+            strict.verify_item(bytes).await,
+            Err(BundlrError::ItemTooLarge)
+        ));
+        Ok(())
+    }
+}
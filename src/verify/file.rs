@@ -1,10 +1,34 @@
-use super::types::{Header, Item};
+use super::types::{BundleStats, Header, Item, ItemTiming, NestedItem};
+use crate::bundle::{read_compressed_section, CompressedSection};
 use crate::error::BundlrError;
+use crate::index::SignerMap;
 use crate::utils::read_offset;
 use crate::BundlrTx;
+use bytes::Bytes;
 use data_encoding::BASE64URL;
+use futures::{
+    executor::block_on,
+    future::{FutureExt, LocalBoxFuture},
+    stream, Stream, StreamExt,
+};
 use primitive_types::U256;
-use std::{cmp, fs::File};
+use sha2::{Digest, Sha256};
+use std::{
+    cmp,
+    collections::HashSet,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Items up to this size are read whole and verified in memory with [`BundlrTx::verify_bytes`]
+const SMALL_ITEM_THRESHOLD: u64 = 4096;
 
 impl From<std::io::Error> for BundlrError {
     fn from(e: std::io::Error) -> Self {
@@ -12,58 +36,606 @@ impl From<std::io::Error> for BundlrError {
     }
 }
 
+/// Verifies every item of the bundle file `filename`. Bundles written by
+/// `bundle::write_bundle_compressed` are detected and, with the `compression` feature,
+/// decompressed transparently. They fail with `BundlrError::Unsupported` otherwise.
 pub async fn verify_file_bundle(filename: String) -> Result<Vec<Item>, BundlrError> {
     let mut file = File::open(&filename)?;
+    if let Some(section) = read_compressed_section(&mut file)? {
+        return verify_compressed_bundle(&mut file, section).await;
+    }
+    let length = file.metadata()?.len();
+    let items = verify_bundle_at(&mut file, 0, length, false).await?;
+    Ok(items.into_iter().map(|nested| nested.item).collect())
+}
 
-    let bundle_length = U256::from_little_endian(&read_offset(&mut file, 0, 32)?).as_u64();
-
-    // NOTE THIS IS UNSAFE BEYOND USIZE LIMIT
-    let header_bytes = read_offset(&mut file, 32, bundle_length as usize * 64)?;
-    // This will use ~100 bytes per header. So 1 GB is 1e+7 headers
-    let mut headers = Vec::with_capacity(cmp::min(bundle_length as usize, 1000));
-
-    for i in (0..(64
-        * usize::try_from(bundle_length)
-            .map_err(|err| BundlrError::TypeParseError(err.to_string()))?))
-        .step_by(64)
-    {
-        let h = Header(
-            U256::from_little_endian(&header_bytes[i..i + 32]).as_u64(),
-            BASE64URL.encode(&header_bytes[i + 32..i + 64]),
-        );
-        headers.push(h);
+/// Verifies every bundle file in `dir`, up to `concurrency` at a time on tokio blocking threads,
+/// returning each file with its number of items or the error it failed with. Subdirectories and
+/// hidden files are skipped. Only failing to read `dir` itself is returned as an error.
+///
+/// Files are verified with [`verify_file_bundle_with_options`], so compressed bundles aren't
+/// supported.
+pub async fn verify_bundle_dir(
+    dir: PathBuf,
+    concurrency: usize,
+) -> Result<Vec<(PathBuf, Result<usize, BundlrError>)>, BundlrError> {
+    let paths = bundle_dir_paths(&dir)?;
+    let results = verify_paths(paths, concurrency)
+        .map(|(path, items)| (path, items.map(|items| items.len())))
+        .collect()
+        .await;
+    Ok(results)
+}
+
+/// Same as [`verify_bundle_dir`], but yields the items of each file as soon as it is verified,
+/// in file name order. Failing to read `dir` is yielded as the error of `dir` itself.
+pub fn verify_bundle_dir_stream(
+    dir: String,
+    concurrency: usize,
+) -> impl Stream<Item = (String, Result<Vec<Item>, BundlrError>)> {
+    let (paths, dir_error) = match bundle_dir_paths(Path::new(&dir)) {
+        Ok(paths) => (paths, None),
+        Err(err) => (vec![], Some((dir, Err(err)))),
+    };
+    let results = verify_paths(paths, concurrency)
+        .map(|(path, items)| (path.to_string_lossy().into_owned(), items));
+    stream::iter(dir_error).chain(results)
+}
+
+/// Bundle files in `dir` in file name order, skipping subdirectories and hidden files
+fn bundle_dir_paths(dir: &Path) -> Result<Vec<PathBuf>, BundlrError> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if entry.file_type()?.is_file() && !hidden {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn verify_paths(
+    paths: Vec<PathBuf>,
+    concurrency: usize,
+) -> impl Stream<Item = (PathBuf, Result<Vec<Item>, BundlrError>)> {
+    stream::iter(paths)
+        .map(|path| async move {
+            // Files already run concurrently, so each is verified by a single thread
+            let options = VerifyOptions {
+                concurrency: 1,
+                ..Default::default()
+            };
+            let filename = path.to_string_lossy().into_owned();
+            let items = tokio::task::spawn_blocking(move || {
+                verify_file_bundle_with_options(&filename, &options)
+            })
+            .await
+            .unwrap_or_else(|err| Err(BundlrError::Unknown(err.to_string())));
+            (path, items)
+        })
+        .buffered(cmp::max(concurrency, 1))
+}
+
+/// Verifies every item of the bundle file `filename`, like [`verify_file_bundle`], then checks
+/// that each was signed by one of the `allowed` owners, compared by
+/// [`BundlrTx::owner_address`]. Returns the ids of the items signed by another owner.
+/// Compressed bundles are not supported.
+pub async fn verify_file_bundle_owned_by(
+    filename: String,
+    allowed: &HashSet<String>,
+) -> Result<Vec<String>, BundlrError> {
+    let mut file = File::open(&filename)?;
+    if read_compressed_section(&mut file)?.is_some() {
+        return Err(BundlrError::Unsupported(
+            "owner checks of compressed bundles".to_owned(),
+        ));
+    }
+    verify_file_bundle(filename).await?;
+
+    let headers = read_headers(&mut file, 0)?;
+    let mut offset = items_start(0, headers.len())?;
+    let mut violations = vec![];
+    for Header(size, id) in &headers {
+        let tx = BundlrTx::from_file_position(&mut file, *size, offset, 4096)?;
+        if !allowed.contains(&tx.owner_address()) {
+            violations.push(id.clone());
+        }
+        offset = next_offset(offset, *size)?;
+    }
+    Ok(violations)
+}
+
+/// Same as [`verify_file_bundle`], but the data of items tagged as bundles (see
+/// [`BundlrTx::is_bundle`]) is recursively verified as a nested bundle.
+pub async fn verify_file_bundle_nested(filename: String) -> Result<Vec<NestedItem>, BundlrError> {
+    let mut file = File::open(&filename)?;
+    let length = file.metadata()?.len();
+    verify_bundle_at(&mut file, 0, length, true).await
+}
+
+/// Options of [`verify_file_bundle_with_options`]
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// Number of items verified at the same time, each on its own thread
+    pub concurrency: usize,
+    /// Maximum number of items verified per second
+    pub rate_limit: Option<usize>,
+    /// Whether a bundle holding several items with the same id fails with
+    /// `BundlrError::InvalidHeaders`, before any item is verified. See [`duplicate_ids`].
+    pub reject_duplicate_ids: bool,
+    /// Maximum size in bytes of an item. A bundle declaring a larger item in its header table
+    /// fails with `BundlrError::ItemTooLarge`, before any item is read.
+    pub max_item_size: Option<u64>,
+    /// Signature types items may be signed with. Items signed with another scheme fail with
+    /// `BundlrError::InvalidSignerType`, before their signature is verified.
+    pub allowed_schemes: Option<HashSet<SignerMap>>,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: thread::available_parallelism().map_or(1, usize::from),
+            rate_limit: None,
+            reject_duplicate_ids: false,
+            max_item_size: None,
+            allowed_schemes: None,
+        }
+    }
+}
+
+impl VerifyOptions {
+    /// Fails with `BundlrError::InvalidSignerType` if the serialized item starting with `bytes`
+    /// is signed with a scheme outside of [`VerifyOptions::allowed_schemes`]
+    pub(crate) fn check_scheme(&self, bytes: &[u8]) -> Result<(), BundlrError> {
+        match &self.allowed_schemes {
+            Some(allowed) if !allowed.contains(&BundlrTx::peek_signer_type(bytes)?) => {
+                Err(BundlrError::InvalidSignerType)
+            }
+            _ => Ok(()),
+        }
     }
+}
 
-    let mut offset = 32 + (64 * bundle_length);
-    let mut items = Vec::with_capacity(cmp::min(bundle_length as usize, 1000));
+/// Same as [`verify_file_bundle`], but items are verified by `options.concurrency` threads,
+/// at most `options.rate_limit` items per second. Concurrency is at least 1, and a rate limit of
+/// 0 fails with `BundlrError::Unsupported`.
+///
+/// This blocks the calling thread until the whole bundle is verified, so async callers should
+/// run it on a blocking-friendly thread.
+pub fn verify_file_bundle_with_options(
+    filename: &str,
+    options: &VerifyOptions,
+) -> Result<Vec<Item>, BundlrError> {
+    verify_file_bundle_with_stats(filename, options).map(|(items, _)| items)
+}
 
+/// Same as [`verify_file_bundle_with_options`], but also returns how long each item took to
+/// read and verify, along with its signature scheme. Time spent waiting for
+/// `options.rate_limit` isn't counted.
+pub fn verify_file_bundle_with_stats(
+    filename: &str,
+    options: &VerifyOptions,
+) -> Result<(Vec<Item>, BundleStats), BundlrError> {
+    if options.rate_limit == Some(0) {
+        return Err(BundlrError::Unsupported(
+            "rate limit of 0 items per second".to_owned(),
+        ));
+    }
+    let mut file = File::open(filename)?;
+    let headers = read_headers(&mut file, 0)?;
+    if let Some(max_item_size) = options.max_item_size {
+        if headers.iter().any(|Header(size, _)| *size > max_item_size) {
+            return Err(BundlrError::ItemTooLarge);
+        }
+    }
+    validate_header_table(&headers, file.metadata()?.len())?;
+    if options.reject_duplicate_ids && !duplicate_ids(&headers).is_empty() {
+        return Err(BundlrError::InvalidHeaders);
+    }
+
+    let mut offset = items_start(0, headers.len())?;
+    let mut jobs = Vec::with_capacity(headers.len());
     for Header(size, id) in headers {
-        // Read 4 KiB - max data-less Bundlr tx
-        // We do it all at once to improve performance - by lowering fs ops and doing ops in memory
-        let mut tx = BundlrTx::from_file_position(&mut file, size, offset, 4096)?;
-
-        match tx.verify().await {
-            Err(err) => return Err(err),
-            Ok(_) => {
-                let sig = tx.get_signarure();
-                let item = Item {
-                    tx_id: id,
-                    signature: sig,
+        jobs.push((offset, size, id));
+        offset = next_offset(offset, size)?;
+    }
+
+    let next_job = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let pacing = options.rate_limit.map(|rate| {
+        (
+            Duration::from_secs_f64(1.0 / rate as f64),
+            Mutex::new(Instant::now()),
+        )
+    });
+
+    let worker = || -> Result<Vec<(usize, Item, ItemTiming)>, BundlrError> {
+        let mut file = File::open(filename)?;
+        let mut small_item = Vec::with_capacity(SMALL_ITEM_THRESHOLD as usize);
+        let mut verified = vec![];
+        while !failed.load(Ordering::Relaxed) {
+            let index = next_job.fetch_add(1, Ordering::Relaxed);
+            let (offset, size, id) = match jobs.get(index) {
+                Some(job) => job,
+                None => break,
+            };
+
+            if let Some((interval, next_slot)) = &pacing {
+                let wait = {
+                    let mut next_slot = next_slot.lock().unwrap();
+                    let now = Instant::now();
+                    let slot = cmp::max(*next_slot, now);
+                    *next_slot = slot + *interval;
+                    slot - now
                 };
-                items.push(item);
-                offset += size;
+                thread::sleep(wait);
+            }
+
+            let start = Instant::now();
+            match verify_item(&mut file, *offset, *size, &mut small_item, options)
+                .and_then(|(signature, scheme)| Ok((checked_item(id.clone(), signature)?, scheme)))
+            {
+                Ok((item, scheme)) => {
+                    let timing = ItemTiming {
+                        tx_id: item.tx_id.clone(),
+                        scheme,
+                        duration: start.elapsed(),
+                    };
+                    verified.push((index, item, timing));
+                }
+                Err(err) => {
+                    failed.store(true, Ordering::Relaxed);
+                    return Err(err);
+                }
             }
         }
+        Ok(verified)
+    };
+
+    let mut verified = thread::scope(|scope| {
+        let workers = (0..options.concurrency.max(1))
+            .map(|_| scope.spawn(worker))
+            .collect::<Vec<_>>();
+        let mut verified = Vec::with_capacity(jobs.len());
+        for worker in workers {
+            let items = worker
+                .join()
+                .map_err(|_| BundlrError::Unknown("Verifier thread panicked".to_string()))??;
+            verified.extend(items);
+        }
+        Ok::<_, BundlrError>(verified)
+    })?;
+
+    verified.sort_by_key(|(index, _, _)| *index);
+    let (items, timings) = verified
+        .into_iter()
+        .map(|(_, item, timing)| (item, timing))
+        .unzip();
+    Ok((items, BundleStats { items: timings }))
+}
+
+/// Same as [`verify_file_bundle`], but items are read into memory and their signatures verified
+/// in parallel on the rayon thread pool. Items are returned in bundle order, and the first
+/// invalid item in that order determines the error.
+///
+/// The whole bundle is held in memory, so this is meant for bundles of CPU-heavy items (e.g.
+/// RSA signed) rather than for bundles of large items.
+#[cfg(feature = "rayon")]
+pub fn verify_file_bundle_rayon(filename: String) -> Result<Vec<Item>, BundlrError> {
+    use rayon::prelude::*;
+
+    let mut file = File::open(&filename)?;
+    let headers = read_headers(&mut file, 0)?;
+    validate_header_table(&headers, file.metadata()?.len())?;
+
+    let mut offset = items_start(0, headers.len())?;
+    let mut items = Vec::with_capacity(headers.len());
+    for Header(size, id) in headers {
+        let mut item = vec![0u8; size as usize];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut item)?;
+        items.push((id, item));
+        offset = next_offset(offset, size)?;
+    }
+
+    items
+        .par_iter()
+        .map(|(id, item)| {
+            BundlrTx::verify_bytes(item)
+                .and_then(|signature| checked_item(id.clone(), signature.to_vec()))
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Reads the header table of a bundle file, i.e. the size and id of every item
+pub fn read_bundle_headers(file: &mut File) -> Result<Vec<Header>, BundlrError> {
+    read_headers(file, 0)
+}
+
+/// Reads the data of the item at `index` of a bundle file, given its header table as read by
+/// [`read_bundle_headers`]. The item itself isn't verified.
+pub fn read_item_data(
+    file: &mut File,
+    headers: &[Header],
+    index: usize,
+) -> Result<Bytes, BundlrError> {
+    let Header(size, _) = headers.get(index).ok_or(BundlrError::NoBytesLeft)?;
+    let offset = headers[..index]
+        .iter()
+        .try_fold(items_start(0, headers.len())?, |offset, Header(size, _)| {
+            next_offset(offset, *size)
+        })?;
+
+    let tx = BundlrTx::from_file_position(file, *size, offset, 4096)?;
+    let header_size = tx.header_size()?;
+    let data_size = size
+        .checked_sub(header_size)
+        .ok_or(BundlrError::NoBytesLeft)?;
+
+    let mut data = vec![0; data_size as usize];
+    file.seek(SeekFrom::Start(offset + header_size))?;
+    file.read_exact(&mut data)?;
+    Ok(data.into())
+}
+
+/// Total size in bytes of a bundle: item count, header table and items. Fails with
+/// `BundlrError::InvalidHeaders` if it overflows.
+pub fn bundle_size_from_headers(headers: &[Header]) -> Result<u64, BundlrError> {
+    headers
+        .iter()
+        .try_fold(items_start(0, headers.len())?, |total, Header(size, _)| {
+            next_offset(total, *size)
+        })
+}
+
+/// Ids shared by several items of a header table, each listed once in order of first
+/// duplication. Indexers keying on ids should not trust bundles for which it isn't empty.
+pub fn duplicate_ids(headers: &[Header]) -> Vec<String> {
+    let mut seen = HashSet::with_capacity(headers.len());
+    let mut duplicates = vec![];
+    for Header(_, id) in headers {
+        if !seen.insert(id) && !duplicates.contains(id) {
+            duplicates.push(id.clone());
+        }
+    }
+    duplicates
+}
+
+/// Checks that the header table accounts for every byte of a bundle of `length` bytes, i.e.
+/// that the item count, the header table and the declared item sizes add up to `length`.
+pub fn validate_header_table(headers: &[Header], length: u64) -> Result<(), BundlrError> {
+    if bundle_size_from_headers(headers)? != length {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    Ok(())
+}
+
+/// Offset of the first item of the bundle starting at `start` with `count` items, failing with
+/// `BundlrError::InvalidHeaders` if it overflows
+fn items_start(start: u64, count: usize) -> Result<u64, BundlrError> {
+    u64::try_from(count)
+        .ok()
+        .and_then(|count| count.checked_mul(64))
+        .and_then(|table| table.checked_add(32))
+        .and_then(|length| length.checked_add(start))
+        .ok_or(BundlrError::InvalidHeaders)
+}
+
+/// Offset of the item following the one of `size` bytes at `offset`, failing with
+/// `BundlrError::InvalidHeaders` if it overflows
+fn next_offset(offset: u64, size: u64) -> Result<u64, BundlrError> {
+    offset.checked_add(size).ok_or(BundlrError::InvalidHeaders)
+}
+
+/// Reads the item count and header table of the bundle starting at `start`. Fails with
+/// `BundlrError::InvalidHeaders` if the header table would extend past the end of the file,
+/// before allocating it.
+fn read_headers(file: &mut File, start: u64) -> Result<Vec<Header>, BundlrError> {
+    let count = U256::from_little_endian(&read_offset(file, start, 32)?);
+    if count > U256::from(u64::MAX) {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    let count = usize::try_from(count.as_u64()).map_err(|_| BundlrError::InvalidHeaders)?;
+    if items_start(start, count)? > file.metadata()?.len() {
+        return Err(BundlrError::InvalidHeaders);
+    }
+
+    let table_length = count.checked_mul(64).ok_or(BundlrError::InvalidHeaders)?;
+    let header_bytes = read_offset(file, start + 32, table_length)?;
+    decode_headers(&header_bytes)
+}
+
+/// Decodes a header table, i.e. 64 bytes per item holding its size and id. Fails with
+/// `BundlrError::InvalidHeaders` if a size doesn't fit in 64 bits.
+fn decode_headers(header_bytes: &[u8]) -> Result<Vec<Header>, BundlrError> {
+    header_bytes
+        .chunks_exact(64)
+        .map(|header| {
+            let size = U256::from_little_endian(&header[..32]);
+            if size > U256::from(u64::MAX) {
+                return Err(BundlrError::InvalidHeaders);
+            }
+            Ok(Header(size.as_u64(), BASE64URL.encode(&header[32..])))
+        })
+        .collect()
+}
+
+/// Decodes the item count and header table at the start of an in-memory bundle
+fn decode_header_table(bundle: &[u8]) -> Result<Vec<Header>, BundlrError> {
+    let count = U256::from_little_endian(bundle.get(..32).ok_or(BundlrError::NoBytesLeft)?);
+    if count > U256::from((bundle.len() - 32) / 64) {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    decode_headers(&bundle[32..32 + 64 * count.as_usize()])
+}
+
+/// Item verified with `signature`, failing with `BundlrError::InvalidHeaders` if its id, the
+/// SHA-256 of the signature, isn't the `id` declared by its header
+fn checked_item(id: String, signature: Vec<u8>) -> Result<Item, BundlrError> {
+    if BASE64URL.encode(&Sha256::digest(&signature)) != id {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    Ok(Item {
+        tx_id: id,
+        signature,
+    })
+}
+
+/// Verifies the item of `size` bytes at `offset`, returning its signature and signature
+/// scheme. Small items are read into `small_item`, which is reused across calls.
+fn verify_item(
+    file: &mut File,
+    offset: u64,
+    size: u64,
+    small_item: &mut Vec<u8>,
+    options: &VerifyOptions,
+) -> Result<(Vec<u8>, SignerMap), BundlrError> {
+    if size <= SMALL_ITEM_THRESHOLD {
+        small_item.resize(size as usize, 0);
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(small_item)?;
+        options.check_scheme(small_item)?;
+        let scheme = BundlrTx::peek_signer_type(small_item)?;
+        let signature = BundlrTx::verify_bytes(small_item)?.to_vec();
+        return Ok((signature, scheme));
+    }
+
+    let signature_type = read_offset(file, offset, 2)?;
+    options.check_scheme(&signature_type)?;
+    let scheme = BundlrTx::peek_signer_type(&signature_type)?;
+    let mut tx = BundlrTx::from_file_position(file, size, offset, 4096)?;
+    block_on(tx.verify())?;
+    Ok((tx.get_signarure(), scheme))
+}
+
+/// Verifies the bundle spanning `length` bytes of `file` from `start`
+fn verify_bundle_at(
+    file: &mut File,
+    start: u64,
+    length: u64,
+    nested: bool,
+) -> LocalBoxFuture<'_, Result<Vec<NestedItem>, BundlrError>> {
+    async move {
+        let headers = read_headers(file, start)?;
+        validate_header_table(&headers, length)?;
+        let offset = items_start(start, headers.len())?;
+        verify_items_at(file, headers, offset, nested).await
+    }
+    .boxed_local()
+}
+
+/// Verifies the items described by `headers`, laid out one after the other from `offset`
+async fn verify_items_at(
+    file: &mut File,
+    headers: Vec<Header>,
+    mut offset: u64,
+    nested: bool,
+) -> Result<Vec<NestedItem>, BundlrError> {
+    let mut items = Vec::with_capacity(cmp::min(headers.len(), 1000));
+    let mut small_item = Vec::with_capacity(SMALL_ITEM_THRESHOLD as usize);
+
+    for Header(size, id) in headers {
+        // Nested bundles need the decoded tags, which the in-memory path skips
+        if !nested && size <= SMALL_ITEM_THRESHOLD {
+            small_item.resize(size as usize, 0);
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut small_item)?;
+
+            let sig = BundlrTx::verify_bytes(&small_item)?;
+            items.push(NestedItem {
+                item: checked_item(id, sig.to_vec())?,
+                children: vec![],
+            });
+            offset = next_offset(offset, size)?;
+            continue;
+        }
+
+        // Read 4 KiB - max data-less Bundlr tx
+        // We do it all at once to improve performance - by lowering fs ops and doing ops in memory
+        let mut tx = BundlrTx::from_file_position(file, size, offset, 4096)?;
+        tx.verify().await?;
+
+        let children = if nested && tx.is_bundle() {
+            let data_start = tx.header_size()?;
+            let data_size = size
+                .checked_sub(data_start)
+                .ok_or(BundlrError::InvalidHeaders)?;
+            verify_bundle_at(file, next_offset(offset, data_start)?, data_size, nested).await?
+        } else {
+            vec![]
+        };
+        items.push(NestedItem {
+            item: checked_item(id, tx.get_signarure())?,
+            children,
+        });
+        offset = next_offset(offset, size)?;
     }
 
     Ok(items)
 }
 
+/// Verifies a bundle written by `bundle::write_bundle_compressed`, whose leading
+/// `section` was already decompressed
+async fn verify_compressed_bundle(
+    file: &mut File,
+    section: CompressedSection,
+) -> Result<Vec<Item>, BundlrError> {
+    let headers = decode_header_table(&section.bytes)?;
+    if !section.data_compressed {
+        if section.bytes.len() != 32 + 64 * headers.len() {
+            return Err(BundlrError::InvalidHeaders);
+        }
+        let data_length = file.metadata()?.len() - section.data_offset;
+        validate_header_table(&headers, section.bytes.len() as u64 + data_length)?;
+        let items = verify_items_at(file, headers, section.data_offset, false).await?;
+        return Ok(items.into_iter().map(|nested| nested.item).collect());
+    }
+
+    validate_header_table(&headers, section.bytes.len() as u64)?;
+    let mut offset = 32 + 64 * headers.len();
+    let mut items = Vec::with_capacity(headers.len());
+    for Header(size, id) in headers {
+        let item = &section.bytes[offset..offset + size as usize];
+        let mut tx = BundlrTx::from_bytes(item.to_vec())?;
+        tx.verify().await?;
+        items.push(checked_item(id, tx.get_signarure())?);
+        offset += size as usize;
+    }
+    Ok(items)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::error::BundlrError;
+    use std::{
+        collections::HashSet,
+        fs::{self, File},
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    use futures::StreamExt;
+    use secp256k1::SecretKey;
+
+    use crate::{
+        error::BundlrError,
+        index::SignerMap,
+        tags::Tag,
+        test_support::{make_bundle, make_item, temp_path, test_signer},
+        utils::u256_le,
+        verify::types::{Header, Item},
+        BundlrTx, Secp256k1Signer, Signer,
+    };
 
-    use super::verify_file_bundle;
+    use super::{
+        bundle_size_from_headers, duplicate_ids, read_bundle_headers, read_item_data,
+        validate_header_table, verify_bundle_dir, verify_bundle_dir_stream, verify_file_bundle,
+        verify_file_bundle_nested, verify_file_bundle_owned_by, verify_file_bundle_with_options,
+        verify_file_bundle_with_stats, VerifyOptions,
+    };
 
     #[tokio::test]
     async fn should_verify_test_bundle() -> Result<(), BundlrError> {
@@ -116,6 +688,462 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_fail_with_trailing_bytes() -> Result<(), BundlrError> {
+        let path = &temp_path("test_bundle_trailing_bytes");
+        let mut bundle = fs::read("./res/test_bundles/solana_sig")?;
+        bundle.extend_from_slice(b"junk");
+        fs::write(path, bundle)?;
+
+        assert!(matches!(
+            verify_file_bundle(path.to_string()).await,
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_reject_overflowing_headers() -> Result<(), BundlrError> {
+        let path = &temp_path("test_bundle_overflowing_headers");
+        let bundle = fs::read("./res/test_bundles/solana_sig")?;
+
+        // Item count whose header table can't fit in the file, or in memory
+        for count in [[0xff; 32], u256_le(u64::MAX)] {
+            let mut hostile = bundle.clone();
+            hostile[..32].copy_from_slice(&count);
+            fs::write(path, &hostile)?;
+            assert!(matches!(
+                verify_file_bundle(path.to_string()).await,
+                Err(BundlrError::InvalidHeaders)
+            ));
+        }
+
+        // Item size not fitting in 64 bits, then summing past u64::MAX
+        let mut hostile = bundle.clone();
+        hostile[32 + 8..32 + 32].fill(0xff);
+        fs::write(path, &hostile)?;
+        assert!(matches!(
+            verify_file_bundle(path.to_string()).await,
+            Err(BundlrError::InvalidHeaders)
+        ));
+        let mut hostile = bundle;
+        hostile[32..32 + 8].fill(0xff);
+        fs::write(path, &hostile)?;
+        assert!(matches!(
+            verify_file_bundle(path.to_string()).await,
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_verify_nested_bundle() -> Result<(), BundlrError> {
+        let path = &temp_path("test_nested_bundle");
+        let signer = test_signer();
+
+        let inner_bundle = make_bundle(&[
+            make_item(&signer, b"hello", &[]).await?,
+            make_item(&signer, &[7u8; 10_000], &[]).await?,
+        ])?;
+
+        let bundle_tags = vec![
+            Tag::new("Bundle-Format", "binary"),
+            Tag::new("Bundle-Version", "2.0.0"),
+        ];
+        let bundle = make_bundle(&[
+            make_item(&signer, &inner_bundle, &bundle_tags).await?,
+            make_item(&signer, b"leaf", &[]).await?,
+        ])?;
+        fs::write(path, bundle)?;
+
+        let items = verify_file_bundle_nested(path.to_string()).await?;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].children.len(), 2);
+        assert!(items[0]
+            .children
+            .iter()
+            .all(|item| item.children.is_empty()));
+        assert!(items[1].children.is_empty());
+
+        assert_eq!(verify_file_bundle(path.to_string()).await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_verify_with_options() -> Result<(), BundlrError> {
+        let path = &temp_path("test_rate_limited_bundle");
+        let signer = test_signer();
+        let mut items = vec![];
+        for i in 0..10 {
+            // Mix items verified in memory and streamed from the file
+            items.push(make_item(&signer, &vec![i; 1000 * i as usize], &[]).await?);
+        }
+        fs::write(path, make_bundle(&items)?)?;
+
+        let expected = verify_file_bundle(path.to_string()).await?;
+        let options = VerifyOptions {
+            concurrency: 4,
+            rate_limit: Some(50),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let verified = verify_file_bundle_with_options(path, &options)?;
+        // 10 items at 50 per second take at least 9 intervals of 20ms
+        assert!(start.elapsed() >= Duration::from_millis(180));
+
+        let ids = |items: &[Item]| items.iter().map(|i| i.tx_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&verified), ids(&expected));
+
+        // Rates beyond u32::MAX items per second don't truncate the pacing interval
+        let options = VerifyOptions {
+            rate_limit: Some(u32::MAX as usize + 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&verify_file_bundle_with_options(path, &options)?),
+            ids(&expected)
+        );
+        let options = VerifyOptions {
+            rate_limit: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_file_bundle_with_options(path, &options),
+            Err(BundlrError::Unsupported(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_collect_item_timings() -> Result<(), BundlrError> {
+        let path = &temp_path("test_item_timings");
+        let ed25519_signer = test_signer();
+        let secp256k1_signer = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        let mut items = vec![];
+        for i in 0..4u8 {
+            let signer: &dyn Signer = if i % 2 == 0 {
+                &ed25519_signer
+            } else {
+                &secp256k1_signer
+            };
+            items.push(make_item(signer, &vec![i; 10_000 * i as usize], &[]).await?);
+        }
+        fs::write(path, make_bundle(&items)?)?;
+
+        let (verified, stats) = verify_file_bundle_with_stats(path, &VerifyOptions::default())?;
+        let ids = verified.iter().map(|i| i.tx_id.clone()).collect::<Vec<_>>();
+        let timed_ids = stats
+            .items
+            .iter()
+            .map(|t| t.tx_id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(timed_ids, ids);
+
+        let by_scheme = stats.by_scheme();
+        assert_eq!(by_scheme.len(), 2);
+        assert_eq!(by_scheme[&ed25519_signer.sig_type()].0, 2);
+        assert_eq!(by_scheme[&secp256k1_signer.sig_type()].0, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "rayon")]
+    async fn should_verify_with_rayon() -> Result<(), BundlrError> {
+        for path in [
+            "./res/test_bundles/arweave_sig",
+            "./res/test_bundles/ethereum_sig",
+        ] {
+            let expected = verify_file_bundle(path.to_string()).await?;
+            let verified = super::verify_file_bundle_rayon(path.to_string())?;
+
+            assert_eq!(verified.len(), expected.len());
+            for (item, expected) in verified.iter().zip(&expected) {
+                assert_eq!(item.tx_id, expected.tx_id);
+                assert_eq!(item.signature, expected.signature);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_compute_bundle_size() -> Result<(), BundlrError> {
+        for path in [
+            "./res/test_bundles/ethereum_sig",
+            "./res/test_bundles/solana_sig",
+        ] {
+            let mut file = File::open(path)?;
+            let headers = read_bundle_headers(&mut file)?;
+            assert_eq!(bundle_size_from_headers(&headers)?, file.metadata()?.len());
+        }
+
+        let headers = vec![
+            Header(u64::MAX, "a".to_string()),
+            Header(1, "b".to_string()),
+        ];
+        assert!(matches!(
+            bundle_size_from_headers(&headers),
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn should_validate_header_table() -> Result<(), BundlrError> {
+        let mut file = File::open("./res/test_bundles/solana_sig")?;
+        let length = file.metadata()?.len();
+        let mut headers = read_bundle_headers(&mut file)?;
+        validate_header_table(&headers, length)?;
+
+        assert!(matches!(
+            validate_header_table(&headers, length + 1),
+            Err(BundlrError::InvalidHeaders)
+        ));
+        headers[0].0 = u64::MAX;
+        assert!(matches!(
+            validate_header_table(&headers, length),
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_detect_duplicate_ids() -> Result<(), BundlrError> {
+        let path = &temp_path("test_duplicate_ids");
+        let signer = test_signer();
+        let item = make_item(&signer, b"hello", &[]).await?;
+        fs::write(path, make_bundle(&[item.clone(), item])?)?;
+
+        let headers = read_bundle_headers(&mut File::open(path)?)?;
+        assert_eq!(duplicate_ids(&headers), vec![headers[0].1.clone()]);
+        assert_eq!(verify_file_bundle(path.to_string()).await?.len(), 2);
+
+        let options = VerifyOptions {
+            reject_duplicate_ids: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_file_bundle_with_options(path, &options),
+            Err(BundlrError::InvalidHeaders)
+        ));
+
+        let solana = read_bundle_headers(&mut File::open("./res/test_bundles/solana_sig")?)?;
+        assert!(duplicate_ids(&solana).is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_reject_items_over_max_size() -> Result<(), BundlrError> {
+        let path = &temp_path("test_max_item_size");
+        let signer = test_signer();
+        let mut bundle = make_bundle(&[
+            make_item(&signer, &[1u8; 10], &[]).await?,
+            make_item(&signer, &[2u8; 100_000], &[]).await?,
+        ])?;
+        fs::write(path, &bundle)?;
+
+        let capped = |max_item_size| VerifyOptions {
+            max_item_size: Some(max_item_size),
+            ..Default::default()
+        };
+        assert_eq!(
+            verify_file_bundle_with_options(path, &capped(200_000))?.len(),
+            2
+        );
+        assert!(matches!(
+            verify_file_bundle_with_options(path, &capped(10_000)),
+            Err(BundlrError::ItemTooLarge)
+        ));
+
+        // Declare a 1 TiB second item: it is rejected from its header, without being read
+        bundle[96..128].fill(0);
+        bundle[96..104].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        fs::write(path, &bundle)?;
+        assert!(matches!(
+            verify_file_bundle_with_options(path, &capped(10_000)),
+            Err(BundlrError::ItemTooLarge)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_reject_disallowed_schemes() -> Result<(), BundlrError> {
+        let path = &temp_path("test_allowed_schemes");
+        let ed25519_signer = test_signer();
+        let secp256k1_signer = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        // A small item, verified in memory, and a large one, verified from the file
+        let bundle = make_bundle(&[
+            make_item(&ed25519_signer, &[1u8; 10], &[]).await?,
+            make_item(&secp256k1_signer, &[2u8; 100_000], &[]).await?,
+        ])?;
+        fs::write(path, bundle)?;
+
+        let allowing = |schemes: &[SignerMap]| VerifyOptions {
+            allowed_schemes: Some(schemes.iter().cloned().collect()),
+            ..Default::default()
+        };
+        let both = allowing(&[ed25519_signer.sig_type(), secp256k1_signer.sig_type()]);
+        assert_eq!(verify_file_bundle_with_options(path, &both)?.len(), 2);
+        for scheme in [ed25519_signer.sig_type(), secp256k1_signer.sig_type()] {
+            assert!(matches!(
+                verify_file_bundle_with_options(path, &allowing(&[scheme])),
+                Err(BundlrError::InvalidSignerType)
+            ));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_read_item_data_by_index() -> Result<(), BundlrError> {
+        let path = &temp_path("test_item_data");
+        let signer = test_signer();
+        let datas = vec![b"first".to_vec(), vec![7u8; 100_000], vec![]];
+        let tags = vec![Tag::new("Content-Type", "application/octet-stream")];
+        let mut items = vec![];
+        for data in &datas {
+            items.push(make_item(&signer, data, &tags).await?);
+        }
+        fs::write(path, make_bundle(&items)?)?;
+
+        let mut file = File::open(path)?;
+        let headers = read_bundle_headers(&mut file)?;
+        for (index, data) in datas.iter().enumerate() {
+            assert_eq!(&read_item_data(&mut file, &headers, index)?[..], &data[..]);
+        }
+        assert!(read_item_data(&mut file, &headers, datas.len()).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_detect_swapped_header_ids() -> Result<(), BundlrError> {
+        let path = &temp_path("test_swapped_ids");
+        let signer = test_signer();
+        let mut bundle = make_bundle(&[
+            make_item(&signer, b"first", &[]).await?,
+            make_item(&signer, b"other", &[]).await?,
+        ])?;
+        // Swap the ids of the first two headers, leaving their sizes and the items untouched
+        let first_id = bundle[64..96].to_vec();
+        bundle.copy_within(128..160, 64);
+        bundle[128..160].copy_from_slice(&first_id);
+        fs::write(path, bundle)?;
+
+        assert!(matches!(
+            verify_file_bundle(path.to_string()).await,
+            Err(BundlrError::InvalidHeaders)
+        ));
+        assert!(matches!(
+            verify_file_bundle_with_options(path, &VerifyOptions::default()),
+            Err(BundlrError::InvalidHeaders)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_verify_bundle_dir() -> Result<(), BundlrError> {
+        let results = verify_bundle_dir(PathBuf::from("./res/gen_bundles"), 8).await?;
+        for (path, result) in results {
+            assert!(result.is_ok(), "{} failed: {:?}", path.display(), result);
+        }
+
+        let dir = PathBuf::from(temp_path("test_verify_bundle_dir"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let mut bundle = make_bundle(&[make_item(&test_signer(), b"hello", &[]).await?])?;
+        fs::write(dir.join("valid"), &bundle)?;
+        let last = bundle.len() - 1;
+        bundle[last] ^= 1;
+        fs::write(dir.join("corrupt"), bundle)?;
+
+        let results = verify_bundle_dir(dir.clone(), 2).await?;
+        fs::remove_dir_all(&dir)?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, dir.join("corrupt"));
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].0, dir.join("valid"));
+        assert_eq!(results[1].1.as_ref().ok(), Some(&1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_stream_bundle_dir_results() -> Result<(), BundlrError> {
+        let dir = PathBuf::from(temp_path("test_verify_bundle_dir_stream"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let signer = test_signer();
+        for i in 1..4u8 {
+            let mut items = vec![];
+            for _ in 0..i {
+                items.push(make_item(&signer, &[i; 10], &[]).await?);
+            }
+            fs::write(dir.join(format!("{}.bundle", i)), make_bundle(&items)?)?;
+        }
+
+        let results = verify_bundle_dir_stream(dir.to_string_lossy().into_owned(), 2)
+            .collect::<Vec<_>>()
+            .await;
+        fs::remove_dir_all(&dir)?;
+        let counts = results
+            .into_iter()
+            .map(|(path, items)| (path, items.unwrap().len()))
+            .collect::<Vec<_>>();
+        let expected = (1..4)
+            .map(|i| {
+                (
+                    dir.join(format!("{}.bundle", i))
+                        .to_string_lossy()
+                        .into_owned(),
+                    i,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(counts, expected);
+
+        let missing = verify_bundle_dir_stream(dir.to_string_lossy().into_owned(), 2)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(missing.len(), 1);
+        assert!(matches!(missing[0].1, Err(BundlrError::FsError(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_flag_items_of_disallowed_owners() -> Result<(), BundlrError> {
+        let path = &temp_path("test_owned_bundle");
+        let allowed_signer = test_signer();
+        let other_signer = Secp256k1Signer::new(
+            SecretKey::from_slice(b"00000000000000000000000000000000").unwrap(),
+        );
+        let mut items = vec![];
+        for i in 0..3u8 {
+            let signer: &dyn Signer = if i == 1 {
+                &other_signer
+            } else {
+                &allowed_signer
+            };
+            items.push(make_item(signer, &[i; 10], &[]).await?);
+        }
+        let allowed = HashSet::from([BundlrTx::from_bytes(items[0].clone())?.owner_address()]);
+        fs::write(path, make_bundle(&items)?)?;
+
+        let mut file = File::open(path)?;
+        let headers = read_bundle_headers(&mut file)?;
+        assert_eq!(
+            verify_file_bundle_owned_by(path.to_string(), &allowed).await?,
+            vec![headers[1].1.clone()]
+        );
+        assert_eq!(
+            verify_file_bundle_owned_by(path.to_string(), &HashSet::new())
+                .await?
+                .len(),
+            3
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_verify_random_bundles() -> Result<(), BundlrError> {
         for i in 1..100 {
@@ -1,13 +1,142 @@
+use std::collections::HashSet;
+
 use bytes::Bytes;
+use data_encoding::BASE64URL_NOPAD;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
 
-use crate::error::BundlrError;
+use crate::{error::BundlrError, utils::with_trailing_slash, BundlrTx};
 
+#[cfg(feature = "verifier")]
+pub mod client;
 pub mod file;
 pub mod types;
 
+#[cfg(feature = "verifier")]
+pub use client::VerifierClient;
+
 pub trait Verifier
 where
     Self: Sized,
 {
     fn verify(pk: Bytes, message: Bytes, signature: Bytes) -> Result<(), BundlrError>;
 }
+
+/// Checks that no two items share the same owner and anchor, which would indicate a replayed
+/// item. Items without anchor are not checked.
+pub fn check_anchor_uniqueness(items: &[BundlrTx]) -> Result<(), BundlrError> {
+    let mut seen = HashSet::with_capacity(items.len());
+    for item in items {
+        if !item.get_anchor().is_empty() && !seen.insert((item.get_owner(), item.get_anchor())) {
+            return Err(BundlrError::InvalidHeaders);
+        }
+    }
+    Ok(())
+}
+
+/// Downloads the raw item `id` from `gateway`, then parses and verifies it. Fails with
+/// `BundlrError::InvalidHeaders` if the id of the downloaded item, the SHA-256 of its
+/// signature, isn't `id`.
+pub async fn verify_id(gateway: Url, id: &str) -> Result<BundlrTx, BundlrError> {
+    let url = with_trailing_slash(gateway)
+        .join(id)
+        .map_err(|err| BundlrError::ParseError(err.to_string()))?;
+    let res = reqwest::get(url)
+        .await
+        .map_err(|err| BundlrError::RequestError(err.to_string()))?;
+    if !res.status().is_success() {
+        return Err(BundlrError::ResponseError(format!(
+            "Status: {}",
+            res.status()
+        )));
+    }
+    let bytes = res
+        .bytes()
+        .await
+        .map_err(|err| BundlrError::ResponseError(err.to_string()))?;
+
+    let mut item = BundlrTx::from_bytes(bytes.to_vec())?;
+    item.verify().await?;
+    if BASE64URL_NOPAD.encode(&Sha256::digest(item.get_signarure())) != id.trim_end_matches('=') {
+        return Err(BundlrError::InvalidHeaders);
+    }
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::BASE64URL_NOPAD;
+    use httpmock::{Method::GET, MockServer};
+    use reqwest::Url;
+    use sha2::{Digest, Sha256};
+
+    use super::{check_anchor_uniqueness, verify_id};
+    use crate::{error::BundlrError, test_support::test_signer, BundlrTx, Ed25519Signer};
+
+    async fn signed_item(signer: &Ed25519Signer, anchor: Vec<u8>) -> BundlrTx {
+        let mut item = BundlrTx::new(vec![], b"hello".to_vec(), vec![]).unwrap();
+        item.set_anchor(anchor).unwrap();
+        item.sign(signer).await.unwrap();
+        item
+    }
+
+    #[tokio::test]
+    async fn should_reject_duplicate_anchors() {
+        let signer = test_signer();
+        let items = vec![
+            signed_item(&signer, vec![1u8; 32]).await,
+            signed_item(&signer, vec![2u8; 32]).await,
+            signed_item(&signer, vec![1u8; 32]).await,
+        ];
+
+        assert!(matches!(
+            check_anchor_uniqueness(&items),
+            Err(BundlrError::InvalidHeaders)
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_accept_unique_anchors() {
+        let signer = test_signer();
+        let other_signer = Ed25519Signer::from_base58("28PmkjeZqLyfRQogb3FU4E1vJh68dXpbojvS2tcPwezZmVQp8zs8ebGmYg1hNRcjX4DkUALf3SkZtytGWPG3vYhs").unwrap();
+        let items = vec![
+            signed_item(&signer, vec![1u8; 32]).await,
+            signed_item(&signer, vec![2u8; 32]).await,
+            signed_item(&other_signer, vec![1u8; 32]).await,
+            signed_item(&signer, vec![]).await,
+            signed_item(&signer, vec![]).await,
+        ];
+
+        assert!(check_anchor_uniqueness(&items).is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_verify_id() -> Result<(), BundlrError> {
+        let signer = test_signer();
+        let tx = signed_item(&signer, vec![]).await;
+        let id = BASE64URL_NOPAD.encode(&Sha256::digest(tx.get_signarure()));
+        let item = tx.as_bytes()?;
+        let mut tampered = item.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path(format!("/{}", id));
+            then.status(200).body(&item);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/tampered");
+            then.status(200).body(&tampered);
+        });
+        let gateway = Url::parse(&server.base_url()).unwrap();
+
+        let verified = verify_id(gateway.clone(), &id).await?;
+        assert_eq!(verified.into_data()?.as_ref(), b"hello");
+        assert!(verify_id(gateway.clone(), "tampered").await.is_err());
+        assert!(matches!(
+            verify_id(gateway.clone(), "missing").await,
+            Err(BundlrError::ResponseError(_))
+        ));
+        Ok(())
+    }
+}
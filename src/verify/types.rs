@@ -1,9 +1,50 @@
+use std::{collections::HashMap, time::Duration};
+
 use serde::{Deserialize, Serialize};
 
+use crate::index::SignerMap;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Item {
     pub tx_id: String,
     pub signature: Vec<u8>,
 }
 
+/// Verified item of a bundle, along with the items it contains when it is a bundle itself
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NestedItem {
+    pub item: Item,
+    pub children: Vec<NestedItem>,
+}
+
 pub struct Header(pub u64, pub String);
+
+/// Time taken to read and verify one item of a bundle
+#[derive(Debug, Clone)]
+pub struct ItemTiming {
+    pub tx_id: String,
+    pub scheme: SignerMap,
+    pub duration: Duration,
+}
+
+/// Per-item verification timings of a bundle, in bundle order, see
+/// [`crate::verify::file::verify_file_bundle_with_stats`]
+#[derive(Debug, Clone, Default)]
+pub struct BundleStats {
+    pub items: Vec<ItemTiming>,
+}
+
+impl BundleStats {
+    /// Number of items and total verification time for each signature scheme
+    pub fn by_scheme(&self) -> HashMap<SignerMap, (usize, Duration)> {
+        let mut totals = HashMap::new();
+        for timing in &self.items {
+            let (count, total) = totals
+                .entry(timing.scheme.clone())
+                .or_insert((0, Duration::ZERO));
+            *count += 1;
+            *total += timing.duration;
+        }
+        totals
+    }
+}
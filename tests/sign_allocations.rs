@@ -0,0 +1,51 @@
+//! Lives in its own test binary, as the tracking global allocator would otherwise apply to every
+//! test of the crate
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use bundlr_sdk::{tags::Tag, BundlrTx, Ed25519Signer};
+
+thread_local! {
+    static TRACK_ALLOCATIONS: Cell<bool> = const { Cell::new(false) };
+    static LARGEST_ALLOCATION: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Records the largest allocation of the current thread while tracking is enabled
+struct TrackingAllocator;
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = TRACK_ALLOCATIONS.try_with(|tracking| {
+            if tracking.get() {
+                LARGEST_ALLOCATION.with(|largest| largest.set(largest.get().max(layout.size())));
+            }
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+#[tokio::test]
+async fn should_sign_without_copying_data() {
+    let secret_key =
+        "kNykCXNxgePDjFbDWjPNvXQRa8U12Ywc19dFVaQ7tebUj3m7H4sF4KKdJwM7yxxb3rqxchdjezX9Szh8bLcQAjb";
+    let signer = Ed25519Signer::from_base58(secret_key).unwrap();
+    let data_len = 8 * 1024 * 1024;
+    let mut data_item =
+        BundlrTx::new(vec![], vec![1u8; data_len], vec![Tag::new("name", "value")]).unwrap();
+
+    LARGEST_ALLOCATION.with(|largest| largest.set(0));
+    TRACK_ALLOCATIONS.with(|tracking| tracking.set(true));
+    let res = data_item.sign(&signer).await;
+    TRACK_ALLOCATIONS.with(|tracking| tracking.set(false));
+
+    assert!(res.is_ok());
+    assert!(LARGEST_ALLOCATION.with(|largest| largest.get()) < data_len);
+    assert!(data_item.verify().await.is_ok());
+}